@@ -0,0 +1,161 @@
+use std::{collections::HashMap, hash::Hash};
+
+/// Where a simulation starts repeating itself: the state after
+/// `start + length` steps matches the one after `start` steps.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Cycle {
+    pub start: usize,
+    pub length: usize,
+}
+
+impl Cycle {
+    /// The step before the end of the first time round the cycle that has
+    /// the same state as step `n`.
+    pub fn equivalent_step(&self, n: usize) -> usize {
+        match n.checked_sub(self.start) {
+            Some(into_cycle) => self.start + into_cycle % self.length,
+            None => n,
+        }
+    }
+
+    /// How many whole cycles step `n` is past its equivalent step.
+    pub fn cycles_skipped(&self, n: usize) -> usize {
+        n.saturating_sub(self.start) / self.length
+    }
+}
+
+// steps until a key repeats or there are `limit` steps of history, giving
+// every state seen along with the cycle if one turned up
+fn run<S, K, F, G>(initial: S, mut step: F, mut key: G, limit: usize) -> (Vec<S>, Option<Cycle>)
+where
+    K: Eq + Hash,
+    F: FnMut(&S) -> S,
+    G: FnMut(&S) -> K,
+{
+    let mut seen = HashMap::from([(key(&initial), 0)]);
+    let mut history = vec![initial];
+    while history.len() <= limit {
+        let next = step(history.last().unwrap());
+        let steps = history.len();
+        history.push(next);
+        if let Some(start) = seen.insert(key(&history[steps]), steps) {
+            let cycle = Cycle {
+                start,
+                length: steps - start,
+            };
+            return (history, Some(cycle));
+        }
+    }
+    (history, None)
+}
+
+/// Steps `initial` along until the `key` of some state matches an earlier
+/// one. Gives the cycle along with every state up to and including the first
+/// repeat. Loops forever if nothing ever repeats.
+pub fn find_cycle<S, K, F, G>(initial: S, step: F, key: G) -> (Cycle, Vec<S>)
+where
+    K: Eq + Hash,
+    F: FnMut(&S) -> S,
+    G: FnMut(&S) -> K,
+{
+    let (history, cycle) = run(initial, step, key, usize::MAX);
+    (cycle.unwrap(), history)
+}
+
+/// The state after `n` steps, skipping ahead once a cycle turns up.
+pub fn state_at<S, K, F, G>(initial: S, step: F, key: G, n: usize) -> S
+where
+    K: Eq + Hash,
+    F: FnMut(&S) -> S,
+    G: FnMut(&S) -> K,
+{
+    let (mut history, cycle) = run(initial, step, key, n);
+    let i = cycle.map_or(n, |c| c.equivalent_step(n));
+    history.swap_remove(i)
+}
+
+/// For states that repeat up to a drift, like a pattern that moves along
+/// or a tower that keeps getting taller. `key` splits a state into its
+/// shape and a numeric offset, and once a shape repeats the offset is taken
+/// to grow by the same amount every time round. Gives the state at the
+/// equivalent step to `n` along with the offset at step `n` itself.
+pub fn offset_at<S, K, F, G>(initial: S, step: F, mut key: G, n: usize) -> (S, i128)
+where
+    K: Eq + Hash,
+    F: FnMut(&S) -> S,
+    G: FnMut(&S) -> (K, i128),
+{
+    let (mut history, cycle) = run(initial, step, |s| key(s).0, n);
+    let Some(cycle) = cycle else {
+        let state = history.swap_remove(n);
+        let offset = key(&state).1;
+        return (state, offset);
+    };
+
+    let drift = key(&history[cycle.start + cycle.length]).1 - key(&history[cycle.start]).1;
+    let state = history.swap_remove(cycle.equivalent_step(n));
+    let offset = key(&state).1 + drift * cycle.cycles_skipped(n) as i128;
+    (state, offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_cycle() {
+        // doubling mod 36 goes 1, 2, 4, 8, 16, 32, 28, 20 and back to 4
+        let (cycle, history) = find_cycle(1u32, |x| x * 2 % 36, |x| *x);
+        assert_eq!(
+            cycle,
+            Cycle {
+                start: 2,
+                length: 6
+            }
+        );
+        assert_eq!(history, vec![1, 2, 4, 8, 16, 32, 28, 20, 4]);
+
+        assert_eq!(cycle.equivalent_step(1), 1);
+        assert_eq!(cycle.equivalent_step(8), 2);
+        assert_eq!(cycle.equivalent_step(1_000_000_001), 5);
+        assert_eq!(cycle.cycles_skipped(1_000_000_001), 166_666_666);
+    }
+
+    #[test]
+    fn test_state_at() {
+        let step = |x: &u64| x * 2 % 36;
+        let naive = |n: usize| (0..n).fold(1, |x, _| step(&x));
+        for n in 0..40 {
+            assert_eq!(state_at(1, step, |x| *x, n), naive(n));
+        }
+        assert_eq!(state_at(1, step, |x| *x, 1_000_000_001), 32);
+
+        // only the key has to repeat, not the whole state
+        let state = state_at(
+            (0u64, 0u64),
+            |(i, total)| (i + 1, total + i),
+            |s| s.0 % 3,
+            100,
+        );
+        assert_eq!(state, (1, 0));
+    }
+
+    #[test]
+    fn test_offset_at() {
+        // a pattern that settles down for a few steps, then drifts two
+        // cells right every step
+        let step = |(t, pos): &(u32, i128)| match t {
+            0..=3 => (t + 1, *pos),
+            _ => (4, pos + 2),
+        };
+        let key = |s: &(u32, i128)| (s.0, s.1);
+        for n in 0..20 {
+            let naive = (0..n).fold((0, 10), |s, _| step(&s));
+            let (state, offset) = offset_at((0, 10), step, key, n);
+            assert_eq!((state.0, offset), naive);
+        }
+        let (state, offset) = offset_at((0, 10), step, key, 50_000_000_000);
+        assert_eq!(state.0, 4);
+        assert_eq!(offset, 10 + 2 * (50_000_000_000 - 4));
+    }
+}
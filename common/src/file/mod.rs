@@ -4,3 +4,7 @@ pub fn read_file(filename: &str, relative_to: &str) -> String {
     let path = Path::new(relative_to).parent().unwrap().join(filename);
     fs::read_to_string(path).expect("Something went wrong reading the file")
 }
+
+pub fn write_file(path: &str, contents: &[u8]) {
+    fs::write(path, contents).expect("Something went wrong writing the file")
+}
@@ -1,3 +1,4 @@
+pub mod cycle;
 pub mod days;
 pub mod file;
 pub mod interface;
@@ -0,0 +1,142 @@
+use super::number_theory::{addmod, modinv, mulmod};
+
+/// The map `x -> a * x + b (mod n)`, with `a` and `b` kept in `0..n`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct AffineMod {
+    pub a: i128,
+    pub b: i128,
+    pub n: i128,
+}
+
+impl AffineMod {
+    pub fn new(a: i128, b: i128, n: i128) -> Self {
+        assert!(n > 0, "Modulus must be positive");
+        Self {
+            a: a.rem_euclid(n),
+            b: b.rem_euclid(n),
+            n,
+        }
+    }
+
+    pub fn identity(n: i128) -> Self {
+        Self::new(1, 0, n)
+    }
+
+    pub fn apply(&self, x: i128) -> i128 {
+        addmod(mulmod(self.a, x, self.n), self.b, self.n)
+    }
+
+    /// The `x` that this maps to `y`, if there's exactly one.
+    pub fn apply_inverse(&self, y: i128) -> Option<i128> {
+        self.inverse().map(|inv| inv.apply(y))
+    }
+
+    /// This map followed by `next`.
+    pub fn then(&self, next: &Self) -> Self {
+        assert_eq!(self.n, next.n, "Maps have different moduli");
+        // next.a * (a * x + b) + next.b
+        Self::new(
+            mulmod(next.a, self.a, self.n),
+            addmod(mulmod(next.a, self.b, self.n), next.b, self.n),
+            self.n,
+        )
+    }
+
+    /// The map undoing this one, which exists when `a` is coprime to `n`.
+    pub fn inverse(&self) -> Option<Self> {
+        // x = a^-1 * y - a^-1 * b
+        let inv = modinv(self.a, self.n)?;
+        Some(Self::new(inv, -mulmod(inv, self.b, self.n), self.n))
+    }
+
+    /// This map applied `times` times over, by repeated squaring.
+    pub fn pow(&self, mut times: u128) -> Self {
+        let mut out = Self::identity(self.n);
+        let mut square = *self;
+        while times > 0 {
+            if times & 1 == 1 {
+                out = out.then(&square);
+            }
+            square = square.then(&square);
+            times >>= 1;
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // where each card ends up after the shuffle techniques
+    fn new_stack(n: i128) -> AffineMod {
+        AffineMod::new(-1, -1, n)
+    }
+
+    fn cut(k: i128, n: i128) -> AffineMod {
+        AffineMod::new(1, -k, n)
+    }
+
+    fn increment(k: i128, n: i128) -> AffineMod {
+        AffineMod::new(k, 0, n)
+    }
+
+    fn deck(shuffle: &AffineMod) -> Vec<i128> {
+        (0..shuffle.n)
+            .map(|position| shuffle.apply_inverse(position).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_shuffles() {
+        let shuffle = increment(7, 10).then(&new_stack(10)).then(&new_stack(10));
+        assert_eq!(deck(&shuffle), vec![0, 3, 6, 9, 2, 5, 8, 1, 4, 7]);
+
+        let shuffle = cut(6, 10).then(&increment(7, 10)).then(&new_stack(10));
+        assert_eq!(deck(&shuffle), vec![3, 0, 7, 4, 1, 8, 5, 2, 9, 6]);
+
+        let shuffle = increment(7, 10).then(&increment(9, 10)).then(&cut(-2, 10));
+        assert_eq!(deck(&shuffle), vec![6, 3, 0, 7, 4, 1, 8, 5, 2, 9]);
+    }
+
+    #[test]
+    fn test_pow() {
+        let shuffle = cut(3, 10007)
+            .then(&increment(31, 10007))
+            .then(&new_stack(10007));
+        let mut repeated = AffineMod::identity(10007);
+        for times in 0..50 {
+            assert_eq!(shuffle.pow(times), repeated);
+            repeated = repeated.then(&shuffle);
+        }
+
+        // a huge deck shuffled a huge number of times, then undone
+        let n = 119315717514047;
+        let times = 101741582076661;
+        let shuffle = increment(54, n).then(&cut(-5928, n)).then(&new_stack(n));
+        let forward = shuffle.pow(times);
+        let back = shuffle.inverse().unwrap().pow(times);
+        assert_eq!(forward.then(&back), AffineMod::identity(n));
+        assert_eq!(forward.apply_inverse(forward.apply(2020)), Some(2020));
+        assert_eq!(back.apply(forward.apply(2020)), 2020);
+    }
+
+    #[test]
+    fn test_huge_modulus() {
+        // a and b both near n, so the sums would overflow without reducing
+        let n = i128::MAX;
+        let map = AffineMod::new(-2, -1, n);
+        assert_eq!(map.apply(3), n - 7);
+        assert_eq!(map.then(&map), AffineMod::new(4, 1, n));
+        assert_eq!(map.pow(n as u128 - 1), AffineMod::identity(n));
+        assert_eq!(map.apply_inverse(map.apply(n - 5)), Some(n - 5));
+    }
+
+    #[test]
+    fn test_not_invertible() {
+        let map = AffineMod::new(4, 1, 10);
+        assert_eq!(map.inverse(), None);
+        assert_eq!(map.apply_inverse(5), None);
+        assert_eq!(map.apply(3), 3);
+    }
+}
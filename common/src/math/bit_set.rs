@@ -0,0 +1,294 @@
+use std::{
+    cmp::Ordering,
+    fmt,
+    ops::{
+        BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not, Shl, ShlAssign, Shr,
+        ShrAssign,
+    },
+};
+
+/// A fixed-width set of `WORDS * 64` bits, which also behaves like an
+/// unsigned integer of that width for shifts and ordering. Bit 0 is the least
+/// significant bit of the first word.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct BitSet<const WORDS: usize> {
+    words: [u64; WORDS],
+}
+
+impl<const WORDS: usize> BitSet<WORDS> {
+    pub const BITS: usize = WORDS * 64;
+    pub const EMPTY: Self = Self { words: [0; WORDS] };
+
+    pub fn new() -> Self {
+        Self::EMPTY
+    }
+
+    pub fn from_u128(val: u128) -> Self {
+        let mut out = Self::new();
+        for (i, word) in out.words.iter_mut().take(2).enumerate() {
+            *word = (val >> (i * 64)) as u64;
+        }
+        assert!(
+            WORDS >= 2 || val >> Self::BITS == 0,
+            "Too big for {} bits: {}",
+            Self::BITS,
+            val
+        );
+        out
+    }
+
+    pub fn as_usize(&self) -> usize {
+        if self.words.iter().skip(1).any(|w| *w != 0) {
+            panic!("Too big for usize: {}", self)
+        }
+        self.words.first().map_or(0, |w| *w as usize)
+    }
+
+    pub fn get(&self, bit: usize) -> bool {
+        assert!(bit < Self::BITS, "Bit out of range: {}", bit);
+        self.words[bit / 64] & (1 << (bit % 64)) != 0
+    }
+
+    pub fn set(&mut self, bit: usize, val: bool) {
+        assert!(bit < Self::BITS, "Bit out of range: {}", bit);
+        if val {
+            self.words[bit / 64] |= 1 << (bit % 64);
+        } else {
+            self.words[bit / 64] &= !(1 << (bit % 64));
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|w| *w == 0)
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn leading_zeros(&self) -> usize {
+        let mut out = 0;
+        for word in self.words.iter().rev() {
+            out += word.leading_zeros() as usize;
+            if *word != 0 {
+                break;
+            }
+        }
+        out
+    }
+
+    pub fn trailing_zeros(&self) -> usize {
+        let mut out = 0;
+        for word in self.words.iter() {
+            out += word.trailing_zeros() as usize;
+            if *word != 0 {
+                break;
+            }
+        }
+        out
+    }
+
+    /// The indices of the set bits, lowest first.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(i, word)| {
+            let mut rest = *word;
+            std::iter::from_fn(move || {
+                if rest == 0 {
+                    return None;
+                }
+                let bit = rest.trailing_zeros() as usize;
+                rest &= rest - 1;
+                Some(i * 64 + bit)
+            })
+        })
+    }
+}
+
+impl<const WORDS: usize> Default for BitSet<WORDS> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const WORDS: usize> FromIterator<usize> for BitSet<WORDS> {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let mut out = Self::new();
+        for bit in iter {
+            out.set(bit, true);
+        }
+        out
+    }
+}
+
+impl<const WORDS: usize> Ord for BitSet<WORDS> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // compare as numbers, most significant word first
+        self.words.iter().rev().cmp(other.words.iter().rev())
+    }
+}
+
+impl<const WORDS: usize> PartialOrd for BitSet<WORDS> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<const WORDS: usize> Shl<usize> for BitSet<WORDS> {
+    type Output = Self;
+
+    fn shl(mut self, bits: usize) -> Self::Output {
+        self.shl_assign(bits);
+        self
+    }
+}
+
+impl<const WORDS: usize> ShlAssign<usize> for BitSet<WORDS> {
+    fn shl_assign(&mut self, bits: usize) {
+        let (word_shift, bit_shift) = (bits / 64, bits % 64);
+        for i in (0..WORDS).rev() {
+            let mut word = 0;
+            if i >= word_shift {
+                word = self.words[i - word_shift] << bit_shift;
+                // only carry bits over when there are some to carry, as a
+                // shift by the full 64 bits would overflow
+                if bit_shift > 0 && i > word_shift {
+                    word |= self.words[i - word_shift - 1] >> (64 - bit_shift);
+                }
+            }
+            self.words[i] = word;
+        }
+    }
+}
+
+impl<const WORDS: usize> Shr<usize> for BitSet<WORDS> {
+    type Output = Self;
+
+    fn shr(mut self, bits: usize) -> Self::Output {
+        self.shr_assign(bits);
+        self
+    }
+}
+
+impl<const WORDS: usize> ShrAssign<usize> for BitSet<WORDS> {
+    fn shr_assign(&mut self, bits: usize) {
+        let (word_shift, bit_shift) = (bits / 64, bits % 64);
+        for i in 0..WORDS {
+            let mut word = 0;
+            if i + word_shift < WORDS {
+                word = self.words[i + word_shift] >> bit_shift;
+                if bit_shift > 0 && i + word_shift + 1 < WORDS {
+                    word |= self.words[i + word_shift + 1] << (64 - bit_shift);
+                }
+            }
+            self.words[i] = word;
+        }
+    }
+}
+
+macro_rules! bitwise_op {
+    ($trait:ident, $method:ident, $assign_trait:ident, $assign_method:ident, $op:tt) => {
+        impl<const WORDS: usize> $trait for BitSet<WORDS> {
+            type Output = Self;
+
+            fn $method(mut self, other: Self) -> Self::Output {
+                self.$assign_method(other);
+                self
+            }
+        }
+
+        impl<const WORDS: usize> $assign_trait for BitSet<WORDS> {
+            fn $assign_method(&mut self, other: Self) {
+                for (word, other) in self.words.iter_mut().zip(other.words) {
+                    *word = *word $op other;
+                }
+            }
+        }
+    };
+}
+
+bitwise_op!(BitAnd, bitand, BitAndAssign, bitand_assign, &);
+bitwise_op!(BitOr, bitor, BitOrAssign, bitor_assign, |);
+bitwise_op!(BitXor, bitxor, BitXorAssign, bitxor_assign, ^);
+
+impl<const WORDS: usize> Not for BitSet<WORDS> {
+    type Output = Self;
+
+    fn not(self) -> Self::Output {
+        Self {
+            words: self.words.map(|w| !w),
+        }
+    }
+}
+
+/// Renders every bit as `#` or `.`, most significant first, the same way as
+/// `u128_to_s`.
+impl<const WORDS: usize> fmt::Display for BitSet<WORDS> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for bit in (0..Self::BITS).rev() {
+            write!(f, "{}", if self.get(bit) { '#' } else { '.' })?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::math::u128_to_s;
+
+    #[test]
+    fn test_get_set() {
+        let mut set: BitSet<3> = [0, 63, 64, 191].into_iter().collect();
+        assert!(set.get(63) && set.get(64) && !set.get(65));
+        assert_eq!(set.count_ones(), 4);
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![0, 63, 64, 191]);
+
+        set.set(63, false);
+        assert_eq!(set.count_ones(), 3);
+        assert_eq!(set.leading_zeros(), 0);
+        assert_eq!(set.trailing_zeros(), 0);
+
+        assert_eq!(BitSet::<3>::new().leading_zeros(), 192);
+        assert_eq!(BitSet::<3>::new().iter().count(), 0);
+        assert!((set ^ set).is_empty());
+        assert_eq!((!set).count_ones(), 189);
+    }
+
+    #[test]
+    fn test_shifts() {
+        let one = BitSet::<4>::from_u128(1);
+        for bits in 0..256 {
+            let shifted = one << bits;
+            assert_eq!(shifted.iter().collect::<Vec<_>>(), vec![bits]);
+            assert_eq!(shifted.trailing_zeros(), bits);
+            assert_eq!(shifted.leading_zeros(), 255 - bits);
+            assert_eq!(shifted >> bits, one);
+        }
+        assert!((one << 256).is_empty());
+        assert!((!one >> 300).is_empty());
+
+        let val = 0x0123_4567_89ab_cdef_fedc_ba98_7654_3210;
+        let set = BitSet::<2>::from_u128(val);
+        for bits in 0..128 {
+            assert_eq!(set << bits, BitSet::from_u128(val << bits));
+            assert_eq!(set >> bits, BitSet::from_u128(val >> bits));
+        }
+    }
+
+    #[test]
+    fn test_ord() {
+        let small = BitSet::<2>::from_u128(u64::MAX as u128);
+        let big = BitSet::<2>::from_u128(1 << 64);
+        assert!(small < big);
+        assert_eq!(big.max(small), big);
+        assert_eq!(big.cmp(&big), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_display() {
+        let val = 0b1011 << 100 | 0b110;
+        assert_eq!(BitSet::<2>::from_u128(val).to_string(), u128_to_s(val));
+        assert_eq!(BitSet::<1>::from_u128(5).to_string().len(), 64);
+    }
+}
@@ -0,0 +1,354 @@
+use std::{
+    cmp::Ordering,
+    fmt,
+    ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Rem, RemAssign, Sub, SubAssign},
+    str::FromStr,
+};
+
+use super::{ParseNumError, U256};
+
+/// A 256-bit signed integer, stored in two's complement.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct I256 {
+    bits: U256,
+}
+
+impl I256 {
+    pub const ZERO: Self = Self { bits: U256::ZERO };
+    pub const ONE: Self = Self { bits: U256::ONE };
+    pub const MIN: Self = Self {
+        bits: U256::LEFTMOST_BIT,
+    };
+    pub const MAX: Self = Self {
+        bits: U256 {
+            left: u128::MAX >> 1,
+            right: u128::MAX,
+        },
+    };
+
+    pub fn from_i128(val: i128) -> Self {
+        let fill = if val < 0 { u128::MAX } else { 0 };
+        Self {
+            bits: U256 {
+                left: fill,
+                right: val as u128,
+            },
+        }
+    }
+
+    pub fn to_i128(&self) -> Option<i128> {
+        let low = self.bits.right as i128;
+        let fill = if low < 0 { u128::MAX } else { 0 };
+        match self.bits.left == fill {
+            true => Some(low),
+            false => None,
+        }
+    }
+
+    /// Reinterprets the bits of an unsigned value, so anything from 2^255 up
+    /// comes out negative.
+    pub fn from_bits(bits: U256) -> Self {
+        Self { bits }
+    }
+
+    pub fn to_bits(&self) -> U256 {
+        self.bits
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.bits.left >> 127 == 1
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.bits.is_zero()
+    }
+
+    pub fn signum(&self) -> i32 {
+        match (self.is_negative(), self.is_zero()) {
+            (true, _) => -1,
+            (false, true) => 0,
+            (false, false) => 1,
+        }
+    }
+
+    /// The magnitude, which always fits even for `MIN`.
+    pub fn unsigned_abs(&self) -> U256 {
+        match self.is_negative() {
+            true => U256::ZERO.wrapping_sub(self.bits),
+            false => self.bits,
+        }
+    }
+
+    pub fn checked_abs(self) -> Option<Self> {
+        match self.is_negative() {
+            true => self.checked_neg(),
+            false => Some(self),
+        }
+    }
+
+    pub fn wrapping_neg(self) -> Self {
+        Self::from_bits(U256::ZERO.wrapping_sub(self.bits))
+    }
+
+    pub fn checked_neg(self) -> Option<Self> {
+        match self == Self::MIN {
+            true => None,
+            false => Some(self.wrapping_neg()),
+        }
+    }
+
+    // the value with this sign and magnitude, if it's in range
+    fn from_sign_magnitude(negative: bool, magnitude: U256) -> Option<Self> {
+        let out = Self::from_bits(magnitude);
+        match negative {
+            false if !out.is_negative() => Some(out),
+            true if magnitude <= Self::MIN.bits => Some(out.wrapping_neg()),
+            _ => None,
+        }
+    }
+
+    pub fn overflowing_add(self, other: Self) -> (Self, bool) {
+        let out = self.wrapping_add(other);
+        let overflow =
+            self.is_negative() == other.is_negative() && out.is_negative() != self.is_negative();
+        (out, overflow)
+    }
+
+    pub fn overflowing_sub(self, other: Self) -> (Self, bool) {
+        let out = self.wrapping_sub(other);
+        let overflow =
+            self.is_negative() != other.is_negative() && out.is_negative() != self.is_negative();
+        (out, overflow)
+    }
+
+    pub fn overflowing_mul(self, other: Self) -> (Self, bool) {
+        let negative = self.is_negative() != other.is_negative();
+        let product = self.unsigned_abs().checked_mul(other.unsigned_abs());
+        match product.and_then(|p| Self::from_sign_magnitude(negative, p)) {
+            Some(out) => (out, false),
+            None => (self.wrapping_mul(other), true),
+        }
+    }
+
+    pub fn wrapping_add(self, other: Self) -> Self {
+        Self::from_bits(self.bits.wrapping_add(other.bits))
+    }
+
+    pub fn wrapping_sub(self, other: Self) -> Self {
+        Self::from_bits(self.bits.wrapping_sub(other.bits))
+    }
+
+    pub fn wrapping_mul(self, other: Self) -> Self {
+        // the low bits of a two's complement product don't depend on sign
+        Self::from_bits(self.bits.wrapping_mul(other.bits))
+    }
+
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        match self.overflowing_add(other) {
+            (out, false) => Some(out),
+            _ => None,
+        }
+    }
+
+    pub fn checked_sub(self, other: Self) -> Option<Self> {
+        match self.overflowing_sub(other) {
+            (out, false) => Some(out),
+            _ => None,
+        }
+    }
+
+    pub fn checked_mul(self, other: Self) -> Option<Self> {
+        match self.overflowing_mul(other) {
+            (out, false) => Some(out),
+            _ => None,
+        }
+    }
+
+    /// Quotient rounded towards zero and a remainder with the sign of
+    /// `self`, like the primitive integers. `None` when dividing by zero or
+    /// for `MIN / -1`.
+    pub fn checked_div_rem(self, other: Self) -> Option<(Self, Self)> {
+        let (q, r) = self.unsigned_abs().checked_div_rem(other.unsigned_abs())?;
+        let quotient = Self::from_sign_magnitude(self.is_negative() != other.is_negative(), q)?;
+        let rem = Self::from_sign_magnitude(self.is_negative(), r)?;
+        Some((quotient, rem))
+    }
+
+    pub fn checked_div(self, other: Self) -> Option<Self> {
+        self.checked_div_rem(other).map(|(q, _)| q)
+    }
+
+    pub fn checked_rem(self, other: Self) -> Option<Self> {
+        self.checked_div_rem(other).map(|(_, r)| r)
+    }
+
+    /// The remainder, moved into `0..other.abs()`.
+    pub fn rem_euclid(self, other: Self) -> Self {
+        let rem = self.unsigned_abs() % other.unsigned_abs();
+        match self.is_negative() && !rem.is_zero() {
+            true => Self::from_bits(other.unsigned_abs() - rem),
+            false => Self::from_bits(rem),
+        }
+    }
+
+    /// `self * other` reduced into `0..m`, for a positive `m`.
+    pub fn mulmod(self, other: Self, m: Self) -> Self {
+        assert!(!m.is_negative() && !m.is_zero(), "Modulus must be positive");
+        let a = self.rem_euclid(m).bits;
+        let b = other.rem_euclid(m).bits;
+        Self::from_bits(a.mulmod(b, m.bits))
+    }
+
+    /// `self` to the power `exp`, reduced into `0..m`, for a positive `m`.
+    pub fn powmod(self, exp: U256, m: Self) -> Self {
+        assert!(!m.is_negative() && !m.is_zero(), "Modulus must be positive");
+        Self::from_bits(self.rem_euclid(m).bits.powmod(exp, m.bits))
+    }
+}
+
+impl From<i128> for I256 {
+    fn from(val: i128) -> Self {
+        Self::from_i128(val)
+    }
+}
+
+impl From<i64> for I256 {
+    fn from(val: i64) -> Self {
+        Self::from_i128(val as i128)
+    }
+}
+
+impl Ord for I256 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // negatives first, and within a sign the bits order the same way
+        other
+            .is_negative()
+            .cmp(&self.is_negative())
+            .then(self.bits.cmp(&other.bits))
+    }
+}
+
+impl PartialOrd for I256 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Neg for I256 {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        self.checked_neg().expect("attempt to negate with overflow")
+    }
+}
+
+arithmetic_ops!(I256);
+
+impl FromStr for I256 {
+    type Err = ParseNumError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+        if digits.starts_with(['+', '-']) {
+            return Err(ParseNumError::InvalidDigit(digits.chars().next().unwrap()));
+        }
+        let magnitude: U256 = digits.parse()?;
+        Self::from_sign_magnitude(negative, magnitude).ok_or(ParseNumError::Overflow)
+    }
+}
+
+impl fmt::Display for I256 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad_integral(!self.is_negative(), "", &self.unsigned_abs().to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arithmetic() {
+        // agrees with i128 where that doesn't overflow
+        let vals = [
+            0,
+            1,
+            -1,
+            7,
+            -7,
+            i64::MIN as i128,
+            i64::MAX as i128,
+            i128::MAX / 5,
+        ];
+        for a in vals {
+            for b in vals {
+                let (x, y) = (I256::from(a), I256::from(b));
+                assert_eq!(x.checked_add(y).unwrap().to_i128(), a.checked_add(b));
+                assert_eq!(x.checked_sub(y).unwrap().to_i128(), a.checked_sub(b));
+                assert_eq!(x.checked_mul(y).unwrap().to_i128(), a.checked_mul(b));
+                assert_eq!(
+                    x.checked_div(y).map(|q| q.to_i128().unwrap()),
+                    a.checked_div(b)
+                );
+                assert_eq!(
+                    x.checked_rem(y).map(|r| r.to_i128().unwrap()),
+                    a.checked_rem(b)
+                );
+                if b != 0 {
+                    assert_eq!(x.rem_euclid(y).to_i128(), Some(a.rem_euclid(b)));
+                }
+                assert_eq!(x.cmp(&y), a.cmp(&b));
+            }
+        }
+
+        assert_eq!(I256::MAX.checked_add(I256::ONE), None);
+        assert_eq!(I256::MAX.wrapping_add(I256::ONE), I256::MIN);
+        assert_eq!(I256::MIN.checked_sub(I256::ONE), None);
+        assert_eq!(I256::MIN.checked_neg(), None);
+        assert_eq!(I256::MIN.checked_div(-I256::ONE), None);
+        assert_eq!(I256::MIN.checked_mul(I256::ONE), Some(I256::MIN));
+        assert_eq!(I256::MIN.checked_mul(-I256::ONE), None);
+        assert_eq!(I256::MIN.unsigned_abs(), U256::LEFTMOST_BIT);
+        assert!(I256::MIN < I256::from(-1i64) && I256::from(-1i64) < I256::MAX);
+    }
+
+    #[test]
+    fn test_mod() {
+        let m = I256::from(1_000_000_007i64);
+        assert_eq!(
+            I256::from(-3i64).mulmod(I256::from(5i64), m),
+            I256::from(1_000_000_007 - 15i64)
+        );
+        assert_eq!(
+            I256::from(-2i64).powmod(U256::from(3u64), m),
+            I256::from(1_000_000_007 - 8i64)
+        );
+    }
+
+    #[test]
+    fn test_parse_display() {
+        for s in [
+            "0",
+            "-1",
+            "57896044618658097711785492504343953926634992332820282019728792003956564819967",
+            "-57896044618658097711785492504343953926634992332820282019728792003956564819968",
+        ] {
+            assert_eq!(s.parse::<I256>().unwrap().to_string(), s);
+        }
+        assert_eq!(
+            "-57896044618658097711785492504343953926634992332820282019728792003956564819968"
+                .parse(),
+            Ok(I256::MIN)
+        );
+        assert_eq!(
+            "57896044618658097711785492504343953926634992332820282019728792003956564819968"
+                .parse::<I256>(),
+            Err(ParseNumError::Overflow)
+        );
+        assert_eq!("--1".parse::<I256>(), Err(ParseNumError::InvalidDigit('-')));
+        assert_eq!(format!("{:+}", I256::from(5i64)), "+5");
+    }
+}
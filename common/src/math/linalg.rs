@@ -0,0 +1,550 @@
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    ops::{Index, IndexMut, Mul},
+};
+
+use num::{Num, One, Zero};
+
+/// A dense matrix, stored row by row. Elimination needs exact division, so
+/// solving and determinants want a field like `Rational`, or `BigRational`
+/// when the numbers get big. Multiplication and powers only need a ring, so
+/// plain or modular integers do for those.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Matrix<T> {
+    rows: usize,
+    cols: usize,
+    cells: Vec<T>,
+}
+
+/// The outcome of solving a linear system.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Solution<T> {
+    Unique(Vec<T>),
+    /// The equations contradict each other.
+    Inconsistent,
+    /// Some unknowns can take any value.
+    Underdetermined,
+}
+
+impl<T> Solution<T> {
+    pub fn unique(self) -> Option<Vec<T>> {
+        match self {
+            Solution::Unique(vals) => Some(vals),
+            _ => None,
+        }
+    }
+}
+
+impl<T: Clone + Zero> Matrix<T> {
+    pub fn new(rows: usize, cols: usize) -> Self {
+        Self {
+            rows,
+            cols,
+            cells: vec![T::zero(); rows * cols],
+        }
+    }
+
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Self {
+        let cols = rows.first().map_or(0, |r| r.len());
+        assert!(
+            rows.iter().all(|r| r.len() == cols),
+            "Rows have different lengths"
+        );
+        Self {
+            rows: rows.len(),
+            cols,
+            cells: rows.into_iter().flatten().collect(),
+        }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn row(&self, row: usize) -> &[T] {
+        &self.cells[row * self.cols..(row + 1) * self.cols]
+    }
+
+    pub fn map<U, F>(&self, f: F) -> Matrix<U>
+    where
+        F: FnMut(&T) -> U,
+    {
+        Matrix {
+            rows: self.rows,
+            cols: self.cols,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+
+    pub fn transpose(&self) -> Self {
+        let mut out = Self::new(self.cols, self.rows);
+        for r in 0..self.rows {
+            for c in 0..self.cols {
+                out[(c, r)] = self[(r, c)].clone();
+            }
+        }
+        out
+    }
+
+    /// The product with a column vector.
+    pub fn apply(&self, vector: &[T]) -> Vec<T>
+    where
+        T: Mul<Output = T>,
+    {
+        assert_eq!(vector.len(), self.cols, "Vector is the wrong length");
+        (0..self.rows)
+            .map(|r| {
+                self.row(r)
+                    .iter()
+                    .zip(vector)
+                    .fold(T::zero(), |acc, (a, b)| acc + a.clone() * b.clone())
+            })
+            .collect()
+    }
+}
+
+impl<T: Clone + Zero + One> Matrix<T> {
+    pub fn identity(size: usize) -> Self {
+        let mut out = Self::new(size, size);
+        for i in 0..size {
+            out[(i, i)] = T::one();
+        }
+        out
+    }
+
+    /// The matrix multiplied by itself `exp` times, by repeated squaring.
+    pub fn pow(&self, mut exp: u64) -> Self {
+        assert_eq!(self.rows, self.cols, "Matrix isn't square");
+        let mut out = Self::identity(self.rows);
+        let mut square = self.clone();
+        while exp > 0 {
+            if exp & 1 == 1 {
+                out = &out * &square;
+            }
+            exp >>= 1;
+            if exp > 0 {
+                square = &square * &square;
+            }
+        }
+        out
+    }
+}
+
+impl<T: Num + Clone> Matrix<T> {
+    fn swap_rows(&mut self, a: usize, b: usize) {
+        for c in 0..self.cols {
+            self.cells.swap(a * self.cols + c, b * self.cols + c);
+        }
+    }
+
+    /// Reduces to reduced row echelon form in place, considering only the
+    /// first `cols` columns for pivots. Returns the pivot column of each
+    /// non-zero row, and the product of the pivots, negated for each row
+    /// swap, which is the determinant when the pivots cover a square matrix.
+    fn eliminate(&mut self, cols: usize) -> (Vec<usize>, T) {
+        let mut pivots = vec![];
+        let mut det = T::one();
+        for c in 0..cols {
+            let r = pivots.len();
+            let Some(pivot) = (r..self.rows).find(|&i| !self[(i, c)].is_zero()) else {
+                continue;
+            };
+            if pivot != r {
+                self.swap_rows(pivot, r);
+                det = T::zero() - det;
+            }
+
+            // scale the pivot to one, then clear the rest of the column
+            let scale = self[(r, c)].clone();
+            det = det * scale.clone();
+            for k in c..self.cols {
+                self[(r, k)] = self[(r, k)].clone() / scale.clone();
+            }
+            for i in 0..self.rows {
+                if i == r || self[(i, c)].is_zero() {
+                    continue;
+                }
+                let factor = self[(i, c)].clone();
+                for k in c..self.cols {
+                    self[(i, k)] = self[(i, k)].clone() - factor.clone() * self[(r, k)].clone();
+                }
+            }
+
+            pivots.push(c);
+            if pivots.len() == self.rows {
+                break;
+            }
+        }
+        (pivots, det)
+    }
+
+    pub fn rank(&self) -> usize {
+        let mut m = self.clone();
+        m.eliminate(self.cols).0.len()
+    }
+
+    pub fn determinant(&self) -> T {
+        assert_eq!(self.rows, self.cols, "Matrix isn't square");
+        let mut m = self.clone();
+        match m.eliminate(self.cols) {
+            (pivots, det) if pivots.len() == self.rows => det,
+            _ => T::zero(),
+        }
+    }
+
+    /// Solves `self * x = b`.
+    pub fn solve(&self, b: &[T]) -> Solution<T> {
+        assert_eq!(b.len(), self.rows, "Wrong number of right-hand sides");
+
+        // the augmented matrix [self | b]
+        let mut m = Self::new(self.rows, self.cols + 1);
+        for r in 0..self.rows {
+            for c in 0..self.cols {
+                m[(r, c)] = self[(r, c)].clone();
+            }
+            m[(r, self.cols)] = b[r].clone();
+        }
+
+        let (pivots, _) = m.eliminate(self.cols);
+        // a leftover row of 0 = non-zero
+        if (pivots.len()..self.rows).any(|r| !m[(r, self.cols)].is_zero()) {
+            return Solution::Inconsistent;
+        }
+        if pivots.len() < self.cols {
+            return Solution::Underdetermined;
+        }
+        Solution::Unique((0..self.cols).map(|r| m[(r, self.cols)].clone()).collect())
+    }
+}
+
+impl<T> Index<(usize, usize)> for Matrix<T> {
+    type Output = T;
+
+    fn index(&self, (row, col): (usize, usize)) -> &Self::Output {
+        assert!(row < self.rows && col < self.cols, "Off the matrix");
+        &self.cells[row * self.cols + col]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Matrix<T> {
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut Self::Output {
+        assert!(row < self.rows && col < self.cols, "Off the matrix");
+        &mut self.cells[row * self.cols + col]
+    }
+}
+
+impl<T: Clone + Zero + Mul<Output = T>> Mul for &Matrix<T> {
+    type Output = Matrix<T>;
+
+    fn mul(self, other: Self) -> Self::Output {
+        assert_eq!(self.cols, other.rows, "Matrix sizes don't match");
+        let mut out = Matrix::<T>::new(self.rows, other.cols);
+        for r in 0..self.rows {
+            for k in 0..self.cols {
+                let a = &self[(r, k)];
+                if a.is_zero() {
+                    continue;
+                }
+                for c in 0..other.cols {
+                    out[(r, c)] = out[(r, c)].clone() + a.clone() * other[(k, c)].clone();
+                }
+            }
+        }
+        out
+    }
+}
+
+/// A linear recurrence over a fixed set of states: every step, each state
+/// passes its count on to the states it leads to, scaled by a weight. Runs
+/// any number of steps in a logarithmic number of matrix products.
+#[derive(Clone, Debug)]
+pub struct Recurrence<S, T> {
+    states: Vec<S>,
+    index: HashMap<S, usize>,
+    // column `from` holds the weights into each `to` row
+    matrix: Matrix<T>,
+}
+
+impl<S, T> Recurrence<S, T>
+where
+    S: Clone + Eq + Hash,
+    T: Clone + Zero + One,
+{
+    /// Built from `(from, to, weight)` transitions, with repeats adding up.
+    /// States are numbered in the order they first appear.
+    pub fn new<I>(transitions: I) -> Self
+    where
+        I: IntoIterator<Item = (S, S, T)>,
+    {
+        let mut states = vec![];
+        let mut index = HashMap::new();
+        let mut id = |state: S| {
+            *index.entry(state.clone()).or_insert_with(|| {
+                states.push(state);
+                states.len() - 1
+            })
+        };
+        let edges: Vec<(usize, usize, T)> = transitions
+            .into_iter()
+            .map(|(from, to, weight)| (id(from), id(to), weight))
+            .collect();
+
+        let mut matrix = Matrix::<T>::new(states.len(), states.len());
+        for (from, to, weight) in edges {
+            matrix[(to, from)] = matrix[(to, from)].clone() + weight;
+        }
+        Self {
+            states,
+            index,
+            matrix,
+        }
+    }
+
+    pub fn states(&self) -> &[S] {
+        &self.states
+    }
+
+    pub fn matrix(&self) -> &Matrix<T> {
+        &self.matrix
+    }
+
+    /// The counts after `steps` steps. Every starting state must be one the
+    /// transitions mention.
+    pub fn run(&self, counts: &HashMap<S, T>, steps: u64) -> HashMap<S, T> {
+        let mut vector = vec![T::zero(); self.states.len()];
+        for (state, count) in counts {
+            let i = *self.index.get(state).expect("Unknown state");
+            vector[i] = vector[i].clone() + count.clone();
+        }
+        let out = self.matrix.pow(steps).apply(&vector);
+        self.states.iter().cloned().zip(out).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use num::BigRational;
+
+    use crate::math::{number_theory::ModInt, Rational};
+
+    fn rational(rows: &[&[i128]]) -> Matrix<Rational> {
+        Matrix::from_rows(rows.iter().map(|r| r.to_vec()).collect()).map(|v| Rational::from(*v))
+    }
+
+    fn integers(vals: &[i128]) -> Vec<Rational> {
+        vals.iter().map(|v| Rational::from(*v)).collect()
+    }
+
+    #[test]
+    fn test_determinant_rank() {
+        let m = rational(&[&[2, -3, 1], &[2, 0, -1], &[1, 4, 5]]);
+        assert_eq!(m.determinant(), Rational::from(49));
+        assert_eq!(m.rank(), 3);
+        assert_eq!(m.transpose().determinant(), Rational::from(49));
+
+        // needs a swap to find a pivot
+        let m = rational(&[&[0, 1], &[1, 0]]);
+        assert_eq!(m.determinant(), Rational::from(-1));
+
+        let m = rational(&[&[1, 2, 3], &[2, 4, 6], &[1, 0, 1]]);
+        assert_eq!(m.determinant(), Rational::from(0));
+        assert_eq!(m.rank(), 2);
+        assert_eq!(rational(&[&[1, 2, 3, 4]]).rank(), 1);
+        assert_eq!(Matrix::<Rational>::identity(4).rank(), 4);
+    }
+
+    #[test]
+    fn test_solve() {
+        let m = rational(&[&[2, 1, -1], &[-3, -1, 2], &[-2, 1, 2]]);
+        let solution = m.solve(&integers(&[8, -11, -3]));
+        assert_eq!(solution, Solution::Unique(integers(&[2, 3, -1])));
+
+        let m = rational(&[&[1, 1], &[1, -1]]);
+        let x = m.solve(&integers(&[1, 0])).unique().unwrap();
+        assert_eq!(x, vec![Rational::new(1, 2), Rational::new(1, 2)]);
+        let product = &m * &Matrix::from_rows(x.into_iter().map(|v| vec![v]).collect());
+        assert_eq!(product, rational(&[&[1], &[0]]));
+
+        let m = rational(&[&[1, 2], &[2, 4]]);
+        assert_eq!(m.solve(&integers(&[3, 6])), Solution::Underdetermined);
+        assert_eq!(m.solve(&integers(&[3, 7])), Solution::Inconsistent);
+
+        // more equations than unknowns, but consistent
+        let m = rational(&[&[1, 0], &[0, 1], &[1, 1]]);
+        assert_eq!(
+            m.solve(&integers(&[2, 5, 7])),
+            Solution::Unique(integers(&[2, 5]))
+        );
+    }
+
+    // the rock (X, Y, Z) @ (VX, VY, VZ) hitting every hailstone. For each
+    // hailstone (P - p) × (V - v) = 0, and subtracting two of those leaves
+    // P × (v1 - v2) + (p1 - p2) × V = p1 × v1 - p2 × v2, which is linear.
+    fn perfect_throw(hail: &[[i128; 6]]) -> Solution<BigRational> {
+        let cross = |a: [i128; 3], b: [i128; 3]| {
+            [
+                a[1] * b[2] - a[2] * b[1],
+                a[2] * b[0] - a[0] * b[2],
+                a[0] * b[1] - a[1] * b[0],
+            ]
+        };
+        let mut rows = vec![];
+        let mut rhs = vec![];
+        for other in [&hail[1], &hail[2]] {
+            let (p1, v1) = (
+                [hail[0][0], hail[0][1], hail[0][2]],
+                [hail[0][3], hail[0][4], hail[0][5]],
+            );
+            let (p2, v2) = (
+                [other[0], other[1], other[2]],
+                [other[3], other[4], other[5]],
+            );
+            let dp = [p1[0] - p2[0], p1[1] - p2[1], p1[2] - p2[2]];
+            let dv = [v1[0] - v2[0], v1[1] - v2[1], v1[2] - v2[2]];
+            rows.push(vec![0, dv[2], -dv[1], 0, -dp[2], dp[1]]);
+            rows.push(vec![-dv[2], 0, dv[0], dp[2], 0, -dp[0]]);
+            rows.push(vec![dv[1], -dv[0], 0, -dp[1], dp[0], 0]);
+            let (c1, c2) = (cross(p1, v1), cross(p2, v2));
+            rhs.extend((0..3).map(|i| c1[i] - c2[i]));
+        }
+        let m = Matrix::from_rows(rows).map(|v| BigRational::from_integer((*v).into()));
+        let b: Vec<BigRational> = rhs
+            .into_iter()
+            .map(|v| BigRational::from_integer(v.into()))
+            .collect();
+        m.solve(&b)
+    }
+
+    fn big(vals: &[i128]) -> Vec<BigRational> {
+        vals.iter()
+            .map(|v| BigRational::from_integer((*v).into()))
+            .collect()
+    }
+
+    #[test]
+    fn test_perfect_throw() {
+        let hail = [
+            [19, 13, 30, -2, 1, -2],
+            [18, 19, 22, -1, -1, -2],
+            [20, 25, 34, -2, -2, -4],
+            [12, 31, 28, -1, -2, -1],
+            [20, 19, 15, 1, -5, -3],
+        ];
+        assert_eq!(
+            perfect_throw(&[hail[0], hail[1], hail[3]]),
+            Solution::Unique(big(&[24, 13, 10, -3, 1, 2]))
+        );
+
+        // puzzle-sized coordinates, hit at times in the hundreds of billions
+        let rock = [
+            172_543_224_455_736,
+            348_373_777_394_510,
+            148_125_938_782_131,
+            -51,
+            -41,
+            72,
+        ];
+        let hail: Vec<[i128; 6]> = [
+            (312_394_123_404, [-110, 232, 25]),
+            (741_110_045_119, [33, -61, 71]),
+            (187_339_990_004, [-284, -99, 201]),
+        ]
+        .into_iter()
+        .map(|(t, v)| {
+            let hit = |i: usize| rock[i] + t * rock[i + 3] - t * v[i];
+            [hit(0), hit(1), hit(2), v[0], v[1], v[2]]
+        })
+        .collect();
+        assert_eq!(perfect_throw(&hail), Solution::Unique(big(&rock)));
+    }
+
+    #[test]
+    fn test_pow() {
+        let fib = Matrix::from_rows(vec![vec![1u128, 1], vec![1, 0]]);
+        assert_eq!(fib.pow(0), Matrix::identity(2));
+        assert_eq!(fib.pow(10)[(0, 1)], 55);
+        assert_eq!(
+            fib.pow(185)[(0, 1)],
+            205697230343233228174223751303346572685
+        );
+
+        type Mod = ModInt<1_000_000_007>;
+        let fib = fib.map(|v| Mod::new(*v as i128));
+        assert_eq!(
+            fib.pow(1_000_000_000_000_000_000)[(0, 1)].value(),
+            209783453
+        );
+    }
+
+    fn run_fish(timers: &[usize], days: u64) -> u64 {
+        // a fish at 0 resets to 6 and spawns one at 8, the rest count down
+        let recurrence = Recurrence::new((0..9).flat_map(|t| match t {
+            0 => vec![(0, 6, 1u64), (0, 8, 1)],
+            _ => vec![(t, t - 1, 1)],
+        }));
+        let mut counts = HashMap::new();
+        for t in timers {
+            *counts.entry(*t).or_insert(0) += 1;
+        }
+        recurrence.run(&counts, days).values().sum()
+    }
+
+    #[test]
+    fn test_lanternfish() {
+        let timers = [3, 4, 3, 1, 2];
+        assert_eq!(run_fish(&timers, 18), 26);
+        assert_eq!(run_fish(&timers, 80), 5934);
+        assert_eq!(run_fish(&timers, 256), 26984457539);
+    }
+
+    fn polymer_score(template: &str, rules: &[(&str, char)], steps: u64) -> u64 {
+        // each pair AB with a rule AB -> C becomes the pairs AC and CB
+        let recurrence = Recurrence::new(rules.iter().flat_map(|(pair, c)| {
+            let (a, b) = (pair.as_bytes()[0] as char, pair.as_bytes()[1] as char);
+            [((a, b), (a, *c), 1u64), ((a, b), (*c, b), 1)]
+        }));
+        let mut pairs = HashMap::new();
+        let chars: Vec<char> = template.chars().collect();
+        for w in chars.windows(2) {
+            *pairs.entry((w[0], w[1])).or_insert(0) += 1;
+        }
+
+        // every element is the start of a pair, apart from the last
+        let mut elements = HashMap::from([(*chars.last().unwrap(), 1)]);
+        for ((a, _), count) in recurrence.run(&pairs, steps) {
+            *elements.entry(a).or_insert(0) += count;
+        }
+        elements.values().max().unwrap() - elements.values().min().unwrap()
+    }
+
+    #[test]
+    fn test_polymer() {
+        let rules = [
+            ("CH", 'B'),
+            ("HH", 'N'),
+            ("CB", 'H'),
+            ("NH", 'C'),
+            ("HB", 'C'),
+            ("HC", 'B'),
+            ("HN", 'C'),
+            ("NN", 'C'),
+            ("BH", 'H'),
+            ("NC", 'B'),
+            ("NB", 'B'),
+            ("BN", 'B'),
+            ("BB", 'N'),
+            ("BC", 'B'),
+            ("CC", 'N'),
+            ("CN", 'C'),
+        ];
+        assert_eq!(polymer_score("NNCB", &rules, 10), 1588);
+        assert_eq!(polymer_score("NNCB", &rules, 40), 2188189693529);
+    }
+}
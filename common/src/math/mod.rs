@@ -1,15 +1,109 @@
-use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, Shl, ShlAssign, Shr, ShrAssign};
+use std::{
+    fmt,
+    ops::{
+        Add, AddAssign, BitAnd, BitAndAssign, BitOr, BitOrAssign, Div, DivAssign, Mul, MulAssign,
+        Rem, RemAssign, Shl, ShlAssign, Shr, ShrAssign, Sub, SubAssign,
+    },
+    str::FromStr,
+};
+
+// the arithmetic operators, which panic on overflow like the primitive ones
+// and are built on the `checked_*` methods
+macro_rules! arithmetic_ops {
+    ($ty:ident) => {
+        arithmetic_ops!(
+            $ty,
+            Add,
+            add,
+            AddAssign,
+            add_assign,
+            checked_add,
+            "attempt to add with overflow"
+        );
+        arithmetic_ops!(
+            $ty,
+            Sub,
+            sub,
+            SubAssign,
+            sub_assign,
+            checked_sub,
+            "attempt to subtract with overflow"
+        );
+        arithmetic_ops!(
+            $ty,
+            Mul,
+            mul,
+            MulAssign,
+            mul_assign,
+            checked_mul,
+            "attempt to multiply with overflow"
+        );
+        arithmetic_ops!(
+            $ty,
+            Div,
+            div,
+            DivAssign,
+            div_assign,
+            checked_div,
+            "attempt to divide by zero or with overflow"
+        );
+        arithmetic_ops!(
+            $ty,
+            Rem,
+            rem,
+            RemAssign,
+            rem_assign,
+            checked_rem,
+            "attempt to calculate the remainder by zero or with overflow"
+        );
+    };
+    ($ty:ident, $trait:ident, $method:ident, $assign_trait:ident, $assign_method:ident, $checked:ident, $msg:expr) => {
+        impl $trait for $ty {
+            type Output = Self;
+
+            fn $method(self, other: Self) -> Self::Output {
+                self.$checked(other).expect($msg)
+            }
+        }
+
+        impl $assign_trait for $ty {
+            fn $assign_method(&mut self, other: Self) {
+                *self = self.$method(other);
+            }
+        }
+    };
+}
+
+mod i256;
+
+pub mod affine;
+pub mod bit_set;
+pub mod linalg;
+pub mod number_theory;
+pub mod sequence;
+
+pub use i256::*;
+
+/// Exact fractions, for when floating point would round away the answer.
+pub type Rational = num::rational::Ratio<i128>;
 
 const U128_LEFTMOST_BIT: u128 = 170141183460469231731687303715884105728;
 
-#[derive(Clone, Copy, Debug)]
+/// A 256-bit unsigned integer.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct U256 {
+    // field order matters for the derived ordering
     left: u128,
     right: u128,
 }
 
 impl U256 {
+    pub const ZERO: Self = Self { left: 0, right: 0 };
     pub const ONE: Self = Self { left: 0, right: 1 };
+    pub const MAX: Self = Self {
+        left: u128::MAX,
+        right: u128::MAX,
+    };
     pub const LEFTMOST_BIT: Self = Self {
         left: U128_LEFTMOST_BIT,
         right: 0,
@@ -32,13 +126,236 @@ impl U256 {
         }
         self.right as usize
     }
+
+    pub fn to_u128(&self) -> Option<u128> {
+        match self.left {
+            0 => Some(self.right),
+            _ => None,
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.left == 0 && self.right == 0
+    }
+
+    pub fn leading_zeros(&self) -> u32 {
+        match self.left {
+            0 => 128 + self.right.leading_zeros(),
+            left => left.leading_zeros(),
+        }
+    }
+
+    fn bit(&self, bit: u32) -> bool {
+        match bit {
+            0..=127 => self.right >> bit & 1 == 1,
+            _ => self.left >> (bit - 128) & 1 == 1,
+        }
+    }
+
+    // least significant first
+    fn to_limbs(self) -> [u64; 4] {
+        [
+            self.right as u64,
+            (self.right >> 64) as u64,
+            self.left as u64,
+            (self.left >> 64) as u64,
+        ]
+    }
+
+    fn from_limbs(limbs: &[u64]) -> Self {
+        Self {
+            left: (limbs[3] as u128) << 64 | limbs[2] as u128,
+            right: (limbs[1] as u128) << 64 | limbs[0] as u128,
+        }
+    }
+
+    pub fn overflowing_add(self, other: Self) -> (Self, bool) {
+        let (right, carry) = self.right.overflowing_add(other.right);
+        let (left, overflow1) = self.left.overflowing_add(other.left);
+        let (left, overflow2) = left.overflowing_add(carry as u128);
+        (Self { left, right }, overflow1 || overflow2)
+    }
+
+    pub fn overflowing_sub(self, other: Self) -> (Self, bool) {
+        let (right, borrow) = self.right.overflowing_sub(other.right);
+        let (left, overflow1) = self.left.overflowing_sub(other.left);
+        let (left, overflow2) = left.overflowing_sub(borrow as u128);
+        (Self { left, right }, overflow1 || overflow2)
+    }
+
+    pub fn overflowing_mul(self, other: Self) -> (Self, bool) {
+        let (a, b) = (self.to_limbs(), other.to_limbs());
+        let mut product = [0u64; 8];
+        for i in 0..4 {
+            let mut carry = 0u128;
+            for j in 0..4 {
+                let t = a[i] as u128 * b[j] as u128 + product[i + j] as u128 + carry;
+                product[i + j] = t as u64;
+                carry = t >> 64;
+            }
+            product[i + 4] = carry as u64;
+        }
+        let overflow = product[4..].iter().any(|l| *l != 0);
+        (Self::from_limbs(&product[..4]), overflow)
+    }
+
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        match self.overflowing_add(other) {
+            (out, false) => Some(out),
+            _ => None,
+        }
+    }
+
+    pub fn checked_sub(self, other: Self) -> Option<Self> {
+        match self.overflowing_sub(other) {
+            (out, false) => Some(out),
+            _ => None,
+        }
+    }
+
+    pub fn checked_mul(self, other: Self) -> Option<Self> {
+        match self.overflowing_mul(other) {
+            (out, false) => Some(out),
+            _ => None,
+        }
+    }
+
+    pub fn checked_div(self, other: Self) -> Option<Self> {
+        self.checked_div_rem(other).map(|(q, _)| q)
+    }
+
+    pub fn checked_rem(self, other: Self) -> Option<Self> {
+        self.checked_div_rem(other).map(|(_, r)| r)
+    }
+
+    pub fn wrapping_add(self, other: Self) -> Self {
+        self.overflowing_add(other).0
+    }
+
+    pub fn wrapping_sub(self, other: Self) -> Self {
+        self.overflowing_sub(other).0
+    }
+
+    pub fn wrapping_mul(self, other: Self) -> Self {
+        self.overflowing_mul(other).0
+    }
+
+    /// Quotient and remainder, or `None` when dividing by zero.
+    pub fn checked_div_rem(self, other: Self) -> Option<(Self, Self)> {
+        if other.is_zero() {
+            return None;
+        }
+        if let (Some(a), Some(b)) = (self.to_u128(), other.to_u128()) {
+            return Some((Self::from_u128(a / b), Self::from_u128(a % b)));
+        }
+
+        // binary long division
+        let mut quotient = Self::ZERO;
+        let mut rem = Self::ZERO;
+        for bit in (0..256 - self.leading_zeros()).rev() {
+            let carry = rem.bit(255);
+            rem <<= 1;
+            rem.right |= self.bit(bit) as u128;
+            if carry || rem >= other {
+                rem = rem.wrapping_sub(other);
+                quotient |= Self::ONE << bit as usize;
+            }
+        }
+        Some((quotient, rem))
+    }
+
+    pub fn div_rem(self, other: Self) -> (Self, Self) {
+        self.checked_div_rem(other)
+            .expect("attempt to divide by zero")
+    }
+
+    // (a + b) % m for a and b already below m
+    fn add_mod(a: Self, b: Self, m: Self) -> Self {
+        match a.overflowing_add(b) {
+            (sum, true) => sum.wrapping_sub(m),
+            (sum, false) if sum >= m => sum - m,
+            (sum, false) => sum,
+        }
+    }
+
+    /// `self * other % m`, without overflowing however big the product is.
+    pub fn mulmod(self, other: Self, m: Self) -> Self {
+        let a = self % m;
+        let b = other % m;
+        if let Some(product) = a.checked_mul(b) {
+            return product % m;
+        }
+
+        // double and add, keeping everything below m
+        let mut out = Self::ZERO;
+        for bit in (0..256 - b.leading_zeros()).rev() {
+            out = Self::add_mod(out, out, m);
+            if b.bit(bit) {
+                out = Self::add_mod(out, a, m);
+            }
+        }
+        out
+    }
+
+    /// `self` to the power `exp`, modulo `m`.
+    pub fn powmod(self, exp: Self, m: Self) -> Self {
+        let base = self % m;
+        let mut out = Self::ONE % m;
+        for bit in (0..256 - exp.leading_zeros()).rev() {
+            out = out.mulmod(out, m);
+            if exp.bit(bit) {
+                out = out.mulmod(base, m);
+            }
+        }
+        out
+    }
+}
+
+impl From<u128> for U256 {
+    fn from(val: u128) -> Self {
+        Self::from_u128(val)
+    }
+}
+
+impl From<u64> for U256 {
+    fn from(val: u64) -> Self {
+        Self::from_u128(val as u128)
+    }
+}
+
+arithmetic_ops!(U256);
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ParseNumError {
+    Empty,
+    InvalidDigit(char),
+    Overflow,
+}
+
+impl FromStr for U256 {
+    type Err = ParseNumError;
+
+    /// Parses a decimal number, with an optional leading `+`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let digits = s.strip_prefix('+').unwrap_or(s);
+        if digits.is_empty() {
+            return Err(ParseNumError::Empty);
+        }
+        let ten = Self::from_u128(10);
+        digits.chars().try_fold(Self::ZERO, |acc, c| {
+            let digit = c.to_digit(10).ok_or(ParseNumError::InvalidDigit(c))?;
+            acc.checked_mul(ten)
+                .and_then(|acc| acc.checked_add(Self::from_u128(digit as u128)))
+                .ok_or(ParseNumError::Overflow)
+        })
+    }
 }
 
 impl Shl<usize> for U256 {
     type Output = Self;
 
     fn shl(self, bits: usize) -> Self::Output {
-        let mut out = self.clone();
+        let mut out = self;
         out.shl_assign(bits);
         out
     }
@@ -46,7 +363,11 @@ impl Shl<usize> for U256 {
 
 impl ShlAssign<usize> for U256 {
     fn shl_assign(&mut self, bits: usize) {
-        if bits >= 128 {
+        if bits == 0 {
+            // nothing to move, and the mask below would shift by 128
+        } else if bits >= 256 {
+            *self = Self::new();
+        } else if bits >= 128 {
             // replace left side with right side, and apply remaining shift
             let rem = bits - 128;
             self.left = self.right << rem;
@@ -65,7 +386,7 @@ impl Shr<usize> for U256 {
     type Output = Self;
 
     fn shr(self, bits: usize) -> Self::Output {
-        let mut out = self.clone();
+        let mut out = self;
         out.shr_assign(bits);
         out
     }
@@ -73,7 +394,11 @@ impl Shr<usize> for U256 {
 
 impl ShrAssign<usize> for U256 {
     fn shr_assign(&mut self, bits: usize) {
-        if bits >= 128 {
+        if bits == 0 {
+            // nothing to move, and the mask below would shift by 128
+        } else if bits >= 256 {
+            *self = Self::new();
+        } else if bits >= 128 {
             // replace right side with left side, and apply remaining shift
             let rem = bits - 128;
             self.right = self.left >> rem;
@@ -92,7 +417,7 @@ impl BitAnd for U256 {
     type Output = Self;
 
     fn bitand(self, other: Self) -> Self::Output {
-        let mut out = self.clone();
+        let mut out = self;
         out.bitand_assign(other);
         out
     }
@@ -109,7 +434,7 @@ impl BitOr for U256 {
     type Output = Self;
 
     fn bitor(self, other: Self) -> Self::Output {
-        let mut out = self.clone();
+        let mut out = self;
         out.bitor_assign(other);
         out
     }
@@ -122,21 +447,28 @@ impl BitOrAssign for U256 {
     }
 }
 
-impl PartialEq<U256> for U256 {
-    fn eq(&self, other: &Self) -> bool {
-        self.left == other.left && self.right == other.right
-    }
-}
-
 impl PartialEq<u128> for U256 {
     fn eq(&self, val: &u128) -> bool {
         self.left == 0 && self.right == *val
     }
 }
 
-impl std::fmt::Display for U256 {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "{} {}", u128_to_s(self.left), u128_to_s(self.right),)
+impl fmt::Display for U256 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // peel off 19 digits at a time, which is as many as fit in a u64
+        let chunk = Self::from_u128(10u128.pow(19));
+        let mut chunks = vec![];
+        let mut rest = *self;
+        while rest >= chunk {
+            let (q, r) = rest.div_rem(chunk);
+            chunks.push(r.right);
+            rest = q;
+        }
+        let mut digits = rest.right.to_string();
+        for c in chunks.iter().rev() {
+            digits.push_str(&format!("{:019}", c));
+        }
+        f.pad_integral(true, "", &digits)
     }
 }
 
@@ -169,3 +501,117 @@ pub fn u8_to_s(num: u8) -> String {
         })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_u256_shifts() {
+        let val = U256::from_u128(5);
+        assert_eq!(val << 0, 5);
+        assert_eq!(val >> 0, 5);
+        assert_eq!((val << 130) >> 130, 5);
+        assert_eq!(val << 256, 0);
+        assert_eq!(U256::LEFTMOST_BIT >> 255, 1);
+    }
+
+    fn big(s: &str) -> U256 {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_u256_arithmetic() {
+        // agrees with u128 where that doesn't overflow
+        let vals = [0, 1, 7, 10u128.pow(19), u64::MAX as u128, u128::MAX / 3];
+        for a in vals {
+            for b in vals {
+                let (x, y) = (U256::from(a), U256::from(b));
+                assert_eq!(x.checked_add(y).unwrap().to_u128(), a.checked_add(b));
+                assert_eq!(
+                    x.checked_sub(y).map(|d| d.to_u128().unwrap()),
+                    a.checked_sub(b)
+                );
+                assert_eq!(x.checked_mul(y).unwrap().to_u128(), a.checked_mul(b));
+                assert_eq!(
+                    x.checked_div(y).map(|q| q.to_u128().unwrap()),
+                    a.checked_div(b)
+                );
+                assert_eq!(
+                    x.checked_rem(y).map(|r| r.to_u128().unwrap()),
+                    a.checked_rem(b)
+                );
+                assert_eq!(x.cmp(&y), a.cmp(&b));
+            }
+        }
+
+        let a = big("1000000000000000000000000000000000000000000000000000000000000000000000");
+        let b = big("98765432109876543210987654321");
+        let (q, r) = a.div_rem(b);
+        assert_eq!(q * b + r, a);
+        assert!(r < b);
+        assert_eq!(
+            (big("3").powmod(big("160"), U256::MAX)
+                / big("1798465042647412146620280340569649349251249"))
+            .to_string(),
+            "12147831364394437333648704227891775"
+        );
+
+        assert_eq!(U256::MAX.checked_add(U256::ONE), None);
+        assert_eq!(U256::MAX.wrapping_add(U256::ONE), U256::ZERO);
+        assert_eq!(U256::ZERO.wrapping_sub(U256::ONE), U256::MAX);
+        assert_eq!(U256::MAX.checked_mul(big("2")), None);
+        assert_eq!(U256::MAX.wrapping_mul(big("2")), U256::MAX - U256::ONE);
+        assert_eq!(U256::ONE.checked_div(U256::ZERO), None);
+    }
+
+    #[test]
+    fn test_u256_mod() {
+        let m = big("10000000000000000000000000000000000000000000000000000000000000000000033");
+        let a = (U256::ONE << 200) % m;
+        let b = big("3").powmod(big("150"), m);
+        assert_eq!(
+            a.mulmod(b, m).to_string(),
+            "1563729680780779266981761699679798454438783353442219680764337198723339"
+        );
+        assert_eq!(
+            big("123456789")
+                .powmod(big("1000000000000000000000000000000"), m)
+                .to_string(),
+            "8794732752567378487243701266363133863087742238295555576890100392303422"
+        );
+
+        // doubling overflows 256 bits along the way
+        let m = (U256::ONE << 255) - big("19") + (U256::ONE << 200);
+        let a = (U256::ONE << 255) - big("19");
+        assert_eq!(
+            a.mulmod(a, m).to_string(),
+            "57895989404687324994213237285288178680711009228766964675387282899722588651501"
+        );
+        assert_eq!(big("5").powmod(U256::ZERO, U256::ONE), U256::ZERO);
+    }
+
+    #[test]
+    fn test_u256_parse_display() {
+        for s in [
+            "0",
+            "10000000000000000000",
+            "57896044618658097711785492504343953926634992332820282019728792003956564819968",
+        ] {
+            assert_eq!(big(s).to_string(), s);
+        }
+        assert_eq!(big("+42"), U256::from(42u64));
+        assert_eq!(
+            U256::MAX.to_string(),
+            "115792089237316195423570985008687907853269984665640564039457584007913129639935"
+        );
+        assert_eq!(format!("{:>5}", U256::from(42u64)), "   42");
+        assert_eq!(
+            "115792089237316195423570985008687907853269984665640564039457584007913129639936"
+                .parse::<U256>(),
+            Err(ParseNumError::Overflow)
+        );
+        assert_eq!("".parse::<U256>(), Err(ParseNumError::Empty));
+        assert_eq!("12a".parse::<U256>(), Err(ParseNumError::InvalidDigit('a')));
+    }
+}
@@ -0,0 +1,315 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    ops::{Add, Mul, Neg, Sub},
+};
+
+use num::{Integer, One, Zero};
+
+use super::U256;
+
+/// Greatest common divisor of every value, or zero for an empty slice.
+pub fn gcd_all<T: Integer + Copy>(vals: &[T]) -> T {
+    vals.iter().fold(T::zero(), |acc, v| acc.gcd(v))
+}
+
+/// Least common multiple of every value, or one for an empty slice.
+pub fn lcm_all<T: Integer + Copy>(vals: &[T]) -> T {
+    vals.iter().fold(T::one(), |acc, v| acc.lcm(v))
+}
+
+/// `(g, x, y)` where `g = gcd(a, b)` and `a * x + b * y = g`.
+pub fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (1, 0);
+    let (mut old_y, mut y) = (0, 1);
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_x, x) = (x, old_x - q * x);
+        (old_y, y) = (y, old_y - q * y);
+    }
+    match old_r < 0 {
+        true => (-old_r, -old_x, -old_y),
+        false => (old_r, old_x, old_y),
+    }
+}
+
+/// The `x` in `0..m` with `a * x ≡ 1 (mod m)`, if `a` and `m` are coprime.
+pub fn modinv(a: i128, m: i128) -> Option<i128> {
+    assert!(m > 0, "Modulus must be positive");
+    match extended_gcd(a.rem_euclid(m), m) {
+        (1, x, _) => Some(x.rem_euclid(m)),
+        _ => None,
+    }
+}
+
+/// `a + b` reduced into `0..m`, without overflowing for moduli near
+/// `i128::MAX`.
+pub fn addmod(a: i128, b: i128, m: i128) -> i128 {
+    assert!(m > 0, "Modulus must be positive");
+    let (a, b) = (a.rem_euclid(m), b.rem_euclid(m));
+    if a >= m - b {
+        a - (m - b)
+    } else {
+        a + b
+    }
+}
+
+/// `a * b` reduced into `0..m`, going through 256 bits when the product
+/// doesn't fit.
+pub fn mulmod(a: i128, b: i128, m: i128) -> i128 {
+    assert!(m > 0, "Modulus must be positive");
+    let (a, b) = (a.rem_euclid(m), b.rem_euclid(m));
+    match a.checked_mul(b) {
+        Some(product) => product % m,
+        None => {
+            let product =
+                U256::from(a as u128).mulmod(U256::from(b as u128), U256::from(m as u128));
+            product.to_u128().unwrap() as i128
+        }
+    }
+}
+
+/// `base` to the power `exp`, reduced into `0..m`.
+pub fn modpow(base: i128, mut exp: u128, m: i128) -> i128 {
+    let mut base = base.rem_euclid(m);
+    let mut out = 1 % m;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            out = mulmod(out, base, m);
+        }
+        base = mulmod(base, base, m);
+        exp >>= 1;
+    }
+    out
+}
+
+/// Solves `x ≡ r (mod m)` for every `(r, m)` at once, giving `x` in
+/// `0..lcm` along with the `lcm` of the moduli. The moduli don't need to be
+/// coprime, but then the congruences might contradict each other, which
+/// gives `None`.
+pub fn crt(congruences: &[(i128, i128)]) -> Option<(i128, i128)> {
+    congruences.iter().try_fold((0, 1), |(x, m), &(r, n)| {
+        assert!(n > 0, "Modulus must be positive");
+        // x + m * k ≡ r (mod n), so m * k ≡ r - x (mod n)
+        let (g, inv, _) = extended_gcd(m, n);
+        let diff = r - x;
+        if diff % g != 0 {
+            return None;
+        }
+        let step = n / g;
+        let k = mulmod(diff / g, inv, step);
+        let lcm = m.checked_mul(step).expect("Combined modulus overflows");
+        Some((addmod(x, mulmod(m, k, lcm), lcm), lcm))
+    })
+}
+
+/// The smallest `x` with `base^x ≡ target (mod m)`, by baby-step
+/// giant-step in about `sqrt(m)` time and space. `base` must be coprime to
+/// `m`.
+pub fn discrete_log(base: i128, target: i128, m: i128) -> Option<u128> {
+    assert!(m > 0, "Modulus must be positive");
+    let target = target.rem_euclid(m);
+    let steps = (m as f64).sqrt().ceil() as i128 + 1;
+
+    // baby steps: base^j for small j, keeping the first j for each value
+    let mut baby = HashMap::new();
+    let mut val = 1 % m;
+    for j in 0..steps {
+        baby.entry(val).or_insert(j);
+        val = mulmod(val, base, m);
+    }
+
+    // giant steps: target * base^(-steps * i)
+    let giant =
+        modinv(modpow(base, steps as u128, m), m).expect("Base must be coprime to the modulus");
+    let mut val = target;
+    for i in 0..steps {
+        if let Some(j) = baby.get(&val) {
+            return Some((i * steps + j) as u128);
+        }
+        val = mulmod(val, giant, m);
+    }
+    None
+}
+
+/// An integer modulo `M`, for counts that only matter modulo something,
+/// like entries of a `Matrix` raised to a huge power.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct ModInt<const M: u64> {
+    val: u64,
+}
+
+impl<const M: u64> ModInt<M> {
+    pub fn new(val: i128) -> Self {
+        Self {
+            val: val.rem_euclid(M as i128) as u64,
+        }
+    }
+
+    pub fn value(&self) -> u64 {
+        self.val
+    }
+
+    pub fn pow(self, exp: u128) -> Self {
+        Self::new(modpow(self.val as i128, exp, M as i128))
+    }
+
+    pub fn inverse(self) -> Option<Self> {
+        modinv(self.val as i128, M as i128).map(Self::new)
+    }
+}
+
+impl<const M: u64> Add for ModInt<M> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self::Output {
+        Self::new(self.val as i128 + other.val as i128)
+    }
+}
+
+impl<const M: u64> Sub for ModInt<M> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self::Output {
+        Self::new(self.val as i128 - other.val as i128)
+    }
+}
+
+impl<const M: u64> Mul for ModInt<M> {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self::Output {
+        Self {
+            val: (self.val as u128 * other.val as u128 % M as u128) as u64,
+        }
+    }
+}
+
+impl<const M: u64> Neg for ModInt<M> {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self::new(-(self.val as i128))
+    }
+}
+
+impl<const M: u64> Zero for ModInt<M> {
+    fn zero() -> Self {
+        Self::new(0)
+    }
+
+    fn is_zero(&self) -> bool {
+        self.val == 0
+    }
+}
+
+impl<const M: u64> One for ModInt<M> {
+    fn one() -> Self {
+        Self::new(1)
+    }
+}
+
+impl<const M: u64> fmt::Display for ModInt<M> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.val)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gcd() {
+        assert_eq!(gcd_all(&[12, 18, 30]), 6);
+        assert_eq!(lcm_all(&[4u64, 6, 10]), 60);
+        assert_eq!(gcd_all::<i32>(&[]), 0);
+        assert_eq!(lcm_all::<i32>(&[]), 1);
+
+        for (a, b) in [(240, 46), (-240, 46), (7, 0), (0, -7), (17, 5)] {
+            let (g, x, y) = extended_gcd(a, b);
+            assert_eq!(g, a.gcd(&b));
+            assert_eq!(a * x + b * y, g);
+        }
+    }
+
+    #[test]
+    fn test_modular() {
+        assert_eq!(addmod(5, 9, 11), 3);
+        assert_eq!(addmod(-5, 3, 11), 9);
+        assert_eq!(
+            addmod(i128::MAX - 1, i128::MAX - 1, i128::MAX),
+            i128::MAX - 2
+        );
+        assert_eq!(modinv(3, 11), Some(4));
+        assert_eq!(modinv(-3, 11), Some(7));
+        assert_eq!(modinv(6, 9), None);
+        assert_eq!(modpow(2, 10, 1000), 24);
+        assert_eq!(modpow(-2, 3, 7), 6);
+        assert_eq!(modpow(5, 0, 1), 0);
+
+        // products that need more than 128 bits
+        let m = 119315717514047;
+        assert_eq!(mulmod(m - 1, m - 1, m), 1);
+        let big = i128::MAX; // 2^127 - 1 is prime
+        assert_eq!(mulmod(big - 1, big - 1, big), 1);
+        assert_eq!(modpow(3, big as u128 - 1, big), 1);
+    }
+
+    #[test]
+    fn test_crt() {
+        // bus departures: t + i ≡ 0 (mod bus)
+        let buses: [(i128, i128); 5] = [(0, 7), (1, 13), (4, 59), (6, 31), (7, 19)];
+        let congruences: Vec<(i128, i128)> = buses.iter().map(|(i, bus)| (-i, *bus)).collect();
+        assert_eq!(crt(&congruences), Some((1068781, 7 * 13 * 59 * 31 * 19)));
+
+        // moduli sharing factors
+        assert_eq!(crt(&[(2, 6), (8, 9)]), Some((8, 18)));
+        assert_eq!(crt(&[(1, 4), (2, 6)]), None);
+        assert_eq!(crt(&[]), Some((0, 1)));
+
+        // moduli multiplying out to nearly 2^126 and 2^127, with the answer
+        // right at the top
+        let p = 9223372036854775783;
+        for q in [9223372036854775643, 18446744073709551557] {
+            assert_eq!(crt(&[(-1, p), (-1, q)]), Some((p * q - 1, p * q)));
+            assert_eq!(
+                crt(&[(p - 1, p), (q - 2, q)]).map(|(x, _)| x % q),
+                Some(q - 2)
+            );
+        }
+    }
+
+    #[test]
+    fn test_discrete_log() {
+        // the card and door public keys
+        assert_eq!(discrete_log(7, 5764801, 20201227), Some(8));
+        assert_eq!(discrete_log(7, 17807724, 20201227), Some(11));
+        assert_eq!(modpow(17807724, 8, 20201227), 14897079);
+
+        assert_eq!(discrete_log(2, 1, 11), Some(0));
+        // 2 only generates {1, 2, 4} mod 7
+        assert_eq!(discrete_log(2, 3, 7), None);
+    }
+
+    #[test]
+    fn test_mod_int() {
+        type Mod7 = ModInt<7>;
+        assert_eq!(Mod7::new(-1).value(), 6);
+        assert_eq!(Mod7::new(5) + Mod7::new(4), Mod7::new(2));
+        assert_eq!(Mod7::new(2) - Mod7::new(5), Mod7::new(4));
+        assert_eq!(Mod7::new(3) * Mod7::new(5), Mod7::one());
+        assert_eq!(-Mod7::new(3), Mod7::new(4));
+        assert_eq!(Mod7::new(3).inverse(), Some(Mod7::new(5)));
+        assert_eq!(Mod7::new(3).pow(6), Mod7::one());
+        assert!(Mod7::new(14).is_zero());
+
+        // big enough that the product needs more than 64 bits
+        type Big = ModInt<{ u64::MAX - 58 }>;
+        let a = Big::new(u64::MAX as i128 - 59);
+        assert_eq!(a * a, Big::one());
+        assert_eq!(a + a, Big::new(-2));
+    }
+}
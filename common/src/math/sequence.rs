@@ -0,0 +1,115 @@
+use super::Rational;
+
+/// The sequence followed by its differences, then their differences, and
+/// so on down to a single value.
+pub fn difference_table(seq: &[i128]) -> Vec<Vec<i128>> {
+    let mut table = vec![seq.to_vec()];
+    while table.last().unwrap().len() > 1 {
+        let row = table.last().unwrap();
+        let next = row.windows(2).map(|w| w[1] - w[0]).collect();
+        table.push(next);
+    }
+    table
+}
+
+/// The next value, assuming the differences eventually settle down.
+pub fn extrapolate_forward(seq: &[i128]) -> i128 {
+    difference_table(seq)
+        .iter()
+        .filter_map(|row| row.last())
+        .sum()
+}
+
+/// The value before the first, assuming the differences eventually settle
+/// down.
+pub fn extrapolate_backward(seq: &[i128]) -> i128 {
+    difference_table(seq)
+        .iter()
+        .filter_map(|row| row.first())
+        .rev()
+        .fold(0, |below, first| first - below)
+}
+
+/// The degree of the polynomial that evenly spaced samples come from, going
+/// by the first row of differences to become constant. At least two values
+/// in that row are needed to be sure, so `None` if there aren't enough
+/// samples.
+pub fn degree(seq: &[i128]) -> Option<usize> {
+    difference_table(seq)
+        .iter()
+        .take_while(|row| row.len() >= 2)
+        .position(|row| row.iter().all(|v| *v == row[0]))
+}
+
+/// The value at `x` of the lowest degree polynomial through every point.
+pub fn lagrange(points: &[(i128, i128)], x: i128) -> Rational {
+    points
+        .iter()
+        .enumerate()
+        .map(|(i, (xi, yi))| {
+            points.iter().enumerate().filter(|(j, _)| *j != i).fold(
+                Rational::from(*yi),
+                |acc, (_, (xj, _))| {
+                    assert_ne!(xi, xj, "Repeated x value: {}", xi);
+                    acc * Rational::new(x - xj, xi - xj)
+                },
+            )
+        })
+        .sum()
+}
+
+/// The value at step `n` of a sequence sampled at steps `0, 1, 2...`, by
+/// fitting a polynomial once the samples reveal its degree. Puzzles that
+/// repeat every `period` steps from some `offset` can pass every `period`th
+/// value and ask for `(step - offset) / period`.
+pub fn predict(samples: &[i128], n: i128) -> Option<i128> {
+    let degree = degree(samples)?;
+    let points: Vec<(i128, i128)> = (0..=degree).map(|i| (i as i128, samples[i])).collect();
+    // integer-valued at consecutive integers means integer-valued everywhere
+    Some(lagrange(&points, n).to_integer())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extrapolate() {
+        let histories = [
+            vec![0, 3, 6, 9, 12, 15],
+            vec![1, 3, 6, 10, 15, 21],
+            vec![10, 13, 16, 21, 30, 45],
+        ];
+        let forward: Vec<i128> = histories.iter().map(|h| extrapolate_forward(h)).collect();
+        assert_eq!(forward, vec![18, 28, 68]);
+        let backward: Vec<i128> = histories.iter().map(|h| extrapolate_backward(h)).collect();
+        assert_eq!(backward, vec![-3, 0, 5]);
+        let degrees: Vec<Option<usize>> = histories.iter().map(|h| degree(h)).collect();
+        assert_eq!(degrees, vec![Some(1), Some(2), Some(3)]);
+
+        assert_eq!(extrapolate_forward(&[]), 0);
+        assert_eq!(extrapolate_forward(&[4]), 4);
+        assert_eq!(degree(&[4, 4]), Some(0));
+        assert_eq!(degree(&[1, 2]), None);
+        assert_eq!(degree(&[1, 2, 4, 8, 16]), None);
+    }
+
+    #[test]
+    fn test_lagrange() {
+        assert_eq!(lagrange(&[(1, 1), (2, 4), (3, 9)], 10), Rational::from(100));
+        assert_eq!(lagrange(&[(0, 0), (2, 1)], 1), Rational::new(1, 2));
+        assert_eq!(lagrange(&[(-3, 5)], 8), Rational::from(5));
+    }
+
+    #[test]
+    fn test_predict() {
+        let f = |n: i128| 3 * n * n - 2 * n + 7;
+        let samples: Vec<i128> = (0..4).map(f).collect();
+        assert_eq!(predict(&samples, 202300), Some(f(202300)));
+        assert_eq!(predict(&samples[..3], 5), None);
+
+        let cubic = |n: i128| n * n * n - 4 * n;
+        let samples: Vec<i128> = (0..6).map(cubic).collect();
+        assert_eq!(predict(&samples, -1000), Some(cubic(-1000)));
+    }
+}
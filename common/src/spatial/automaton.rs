@@ -0,0 +1,506 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Debug,
+    str::FromStr,
+};
+
+use num::PrimInt;
+
+use super::{Connectivity, Grid};
+
+/// Anything that evolves one generation at a time.
+pub trait Automaton {
+    /// Moves on a generation, returning whether any cell changed.
+    fn step(&mut self) -> bool;
+
+    fn generation(&self) -> usize;
+
+    fn run(&mut self, generations: usize) {
+        for _ in 0..generations {
+            self.step();
+        }
+    }
+
+    /// Steps until nothing changes, returning the generation that turned
+    /// out to be stable.
+    fn run_until_stable(&mut self) -> usize {
+        while self.step() {}
+        self.generation() - 1
+    }
+}
+
+/// Which cells count as neighbours on a dense grid.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Neighbourhood {
+    /// The four orthogonal cells.
+    VonNeumann,
+    /// All eight surrounding cells.
+    Moore,
+    /// The first cell seen in each of the eight directions, looking past
+    /// any empty cells.
+    LineOfSight,
+}
+
+/// An automaton over every cell of a grid. Empty cells aren't part of the
+/// world: they never change and are never anyone's neighbour.
+#[derive(Clone, Debug)]
+pub struct DenseAutomaton<T, S, F> {
+    current: Grid<T, S>,
+    next: Grid<T, S>,
+    // neighbours of cell i are neighbours[starts[i]..starts[i + 1]]
+    starts: Vec<usize>,
+    neighbours: Vec<usize>,
+    rule: F,
+    generation: usize,
+}
+
+impl<T, S, F> DenseAutomaton<T, S, F>
+where
+    T: FromStr + PrimInt,
+    <T as FromStr>::Err: Debug,
+    S: Clone + Eq,
+    F: FnMut(&S, &[S]) -> S,
+{
+    /// `rule` gets each cell's state and its neighbours' states, and returns
+    /// its next state.
+    pub fn new(grid: Grid<T, S>, neighbourhood: Neighbourhood, rule: F) -> Self {
+        Self::with_neighbours(
+            grid,
+            |grid, i| match neighbourhood {
+                Neighbourhood::VonNeumann => {
+                    grid.connected_neighbours(i, Connectivity::Four).collect()
+                }
+                Neighbourhood::Moore => grid.connected_neighbours(i, Connectivity::Eight).collect(),
+                Neighbourhood::LineOfSight => Self::line_of_sight(grid, i),
+            },
+            rule,
+        )
+    }
+
+    /// For any other neighbourhood. Neighbours are worked out once up front,
+    /// and empty cells are filtered out.
+    pub fn with_neighbours<N>(grid: Grid<T, S>, mut neighbours_of: N, rule: F) -> Self
+    where
+        N: FnMut(&Grid<T, S>, usize) -> Vec<usize>,
+    {
+        let mut starts = vec![0];
+        let mut neighbours = vec![];
+        for i in 0..grid.cells.len() {
+            if grid.cells[i].is_some() {
+                let found = neighbours_of(&grid, i);
+                neighbours.extend(found.into_iter().filter(|n| grid.cells[*n].is_some()));
+            }
+            starts.push(neighbours.len());
+        }
+
+        Self {
+            next: grid.clone(),
+            current: grid,
+            starts,
+            neighbours,
+            rule,
+            generation: 0,
+        }
+    }
+
+    fn line_of_sight(grid: &Grid<T, S>, index: usize) -> Vec<usize> {
+        let (width, height) = (grid.width as i64, grid.height as i64);
+        let (x, y) = ((index % grid.width) as i64, (index / grid.width) as i64);
+
+        let mut out = vec![];
+        for (dx, dy) in [
+            (-1, -1),
+            (0, -1),
+            (1, -1),
+            (-1, 0),
+            (1, 0),
+            (-1, 1),
+            (0, 1),
+            (1, 1),
+        ] {
+            let (mut cx, mut cy) = (x + dx, y + dy);
+            while (0..width).contains(&cx) && (0..height).contains(&cy) {
+                let i = (cy * width + cx) as usize;
+                if grid.cells[i].is_some() {
+                    out.push(i);
+                    break;
+                }
+                cx += dx;
+                cy += dy;
+            }
+        }
+        out
+    }
+
+    pub fn grid(&self) -> &Grid<T, S> {
+        &self.current
+    }
+
+    pub fn count<P>(&self, mut predicate: P) -> usize
+    where
+        P: FnMut(&S) -> bool,
+    {
+        self.current.iter().filter(|(_, s)| predicate(s)).count()
+    }
+}
+
+impl<T, S, F> Automaton for DenseAutomaton<T, S, F>
+where
+    T: FromStr + PrimInt,
+    <T as FromStr>::Err: Debug,
+    S: Clone + Eq,
+    F: FnMut(&S, &[S]) -> S,
+{
+    fn step(&mut self) -> bool {
+        let mut changed = false;
+        let mut states = vec![];
+
+        for i in 0..self.current.cells.len() {
+            if let Some(state) = &self.current.cells[i] {
+                states.clear();
+                states.extend(
+                    self.neighbours[self.starts[i]..self.starts[i + 1]]
+                        .iter()
+                        .map(|n| self.current.cells[*n].clone().unwrap()),
+                );
+                let next = (self.rule)(state, &states);
+                changed |= next != *state;
+                self.next.cells[i] = Some(next);
+            }
+        }
+
+        std::mem::swap(&mut self.current, &mut self.next);
+        self.generation += 1;
+        changed
+    }
+
+    fn generation(&self) -> usize {
+        self.generation
+    }
+}
+
+/// Offsets to the `2 * N` orthogonal neighbours in `N` dimensions.
+pub fn von_neumann<const N: usize>() -> Vec<[i64; N]> {
+    let mut out = vec![];
+    for axis in 0..N {
+        for delta in [-1, 1] {
+            let mut offset = [0; N];
+            offset[axis] = delta;
+            out.push(offset);
+        }
+    }
+    out
+}
+
+/// Offsets to the `3^N - 1` surrounding cells in `N` dimensions.
+pub fn moore<const N: usize>() -> Vec<[i64; N]> {
+    let mut out = vec![[0; N]];
+    for axis in 0..N {
+        out = out
+            .into_iter()
+            .flat_map(|offset| {
+                [-1, 0, 1].map(|delta| {
+                    let mut offset = offset;
+                    offset[axis] = delta;
+                    offset
+                })
+            })
+            .collect();
+    }
+    out.retain(|offset| offset.iter().any(|d| *d != 0));
+    out
+}
+
+/// Offsets to the six neighbours of a hex tile, in axial coordinates.
+pub const HEX: [[i64; 2]; 6] = [[1, 0], [-1, 0], [0, 1], [0, -1], [1, -1], [-1, 1]];
+
+/// An automaton over unbounded space, only storing cells that aren't in the
+/// default state. The rule has to leave a default cell with only default
+/// neighbours alone, since those are never looked at.
+#[derive(Clone, Debug)]
+pub struct SparseAutomaton<const N: usize, S, F> {
+    cells: HashMap<[i64; N], S>,
+    next: HashMap<[i64; N], S>,
+    candidates: HashSet<[i64; N]>,
+    offsets: Vec<[i64; N]>,
+    default: S,
+    rule: F,
+    generation: usize,
+}
+
+impl<const N: usize, S, F> SparseAutomaton<N, S, F>
+where
+    S: Clone + Eq,
+    F: FnMut(&S, &[S]) -> S,
+{
+    pub fn new<I>(cells: I, default: S, offsets: Vec<[i64; N]>, rule: F) -> Self
+    where
+        I: IntoIterator<Item = ([i64; N], S)>,
+    {
+        let cells = cells
+            .into_iter()
+            .filter(|(_, state)| *state != default)
+            .collect();
+
+        Self {
+            cells,
+            next: HashMap::new(),
+            candidates: HashSet::new(),
+            offsets,
+            default,
+            rule,
+            generation: 0,
+        }
+    }
+
+    fn shifted(pos: &[i64; N], offset: &[i64; N]) -> [i64; N] {
+        let mut out = *pos;
+        for (o, d) in out.iter_mut().zip(offset) {
+            *o += d;
+        }
+        out
+    }
+
+    pub fn get(&self, pos: &[i64; N]) -> &S {
+        self.cells.get(pos).unwrap_or(&self.default)
+    }
+
+    /// Every cell not in the default state.
+    pub fn cells(&self) -> impl Iterator<Item = (&[i64; N], &S)> {
+        self.cells.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+}
+
+impl<const N: usize, S, F> Automaton for SparseAutomaton<N, S, F>
+where
+    S: Clone + Eq,
+    F: FnMut(&S, &[S]) -> S,
+{
+    fn step(&mut self) -> bool {
+        // only cells near something interesting can change
+        self.candidates.clear();
+        for pos in self.cells.keys() {
+            self.candidates.insert(*pos);
+            for offset in &self.offsets {
+                self.candidates.insert(Self::shifted(pos, offset));
+            }
+        }
+
+        let mut changed = false;
+        let mut states = vec![];
+        self.next.clear();
+
+        for pos in &self.candidates {
+            states.clear();
+            states.extend(self.offsets.iter().map(|offset| {
+                let neighbour = Self::shifted(pos, offset);
+                self.cells.get(&neighbour).unwrap_or(&self.default).clone()
+            }));
+
+            let state = self.cells.get(pos).unwrap_or(&self.default);
+            let next = (self.rule)(state, &states);
+            changed |= next != *state;
+            if next != self.default {
+                self.next.insert(*pos, next);
+            }
+        }
+
+        std::mem::swap(&mut self.cells, &mut self.next);
+        self.generation += 1;
+        changed
+    }
+
+    fn generation(&self) -> usize {
+        self.generation
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use indoc::indoc;
+
+    const SEATS: &str = indoc! {"
+        L.LL.LL.LL
+        LLLLLLL.LL
+        L.L.L..L..
+        LLLL.LL.LL
+        L.LL.LL.LL
+        L.LLLLL.LL
+        ..L.L.....
+        LLLLLLLLLL
+        L.LLLLLL.L
+        L.LLLLL.LL
+    "};
+
+    fn seating(neighbourhood: Neighbourhood, tolerance: usize) -> usize {
+        // floor isn't part of the world at all
+        let grid: Grid<u8, bool> = Grid::parse(SEATS, |c, _| match c {
+            'L' => Some(false),
+            '#' => Some(true),
+            _ => None,
+        });
+        let mut seats = DenseAutomaton::new(grid, neighbourhood, |occupied, neighbours| {
+            let count = neighbours.iter().filter(|n| **n).count();
+            if *occupied {
+                count < tolerance
+            } else {
+                count == 0
+            }
+        });
+        seats.run_until_stable();
+        seats.count(|occupied| *occupied)
+    }
+
+    #[test]
+    fn test_seating() {
+        assert_eq!(seating(Neighbourhood::Moore, 4), 37);
+        assert_eq!(seating(Neighbourhood::LineOfSight, 5), 26);
+    }
+
+    #[test]
+    fn test_until_stable() {
+        // a lone cell dies straight away, and nothing changes after that
+        let mut grid: Grid<u8, bool> = Grid::parse("...\n...\n...", |_, _| Some(false));
+        grid.set(4, true);
+        let mut life = DenseAutomaton::new(grid, Neighbourhood::VonNeumann, |alive, n| {
+            let count = n.iter().filter(|n| **n).count();
+            count == 3 || (*alive && count == 2)
+        });
+        assert_eq!(life.run_until_stable(), 1);
+        assert_eq!(life.generation(), 2);
+        assert_eq!(life.count(|alive| *alive), 0);
+    }
+
+    fn life_rule(alive: &bool, neighbours: &[bool]) -> bool {
+        let count = neighbours.iter().filter(|n| **n).count();
+        count == 3 || (*alive && count == 2)
+    }
+
+    fn conway_cubes<const N: usize>() -> usize {
+        let start = indoc! {"
+            .#.
+            ..#
+            ###
+        "};
+        let cells = start.lines().enumerate().flat_map(|(y, line)| {
+            line.chars()
+                .enumerate()
+                .filter(|(_, c)| *c == '#')
+                .map(move |(x, _)| {
+                    let mut pos = [0; N];
+                    pos[0] = x as i64;
+                    pos[1] = y as i64;
+                    (pos, true)
+                })
+        });
+        let mut cubes = SparseAutomaton::new(cells, false, moore::<N>(), life_rule);
+        cubes.run(6);
+        cubes.len()
+    }
+
+    #[test]
+    fn test_conway_cubes() {
+        assert_eq!(conway_cubes::<3>(), 112);
+        assert_eq!(conway_cubes::<4>(), 848);
+    }
+
+    #[test]
+    fn test_offsets() {
+        assert_eq!(von_neumann::<3>().len(), 6);
+        assert_eq!(moore::<2>().len(), 8);
+        assert_eq!(moore::<4>().len(), 80);
+        assert!(!moore::<3>().contains(&[0, 0, 0]));
+    }
+
+    #[test]
+    fn test_blinker() {
+        let cells = [[0, -1], [0, 0], [0, 1]].map(|pos| (pos, true));
+        let mut life = SparseAutomaton::new(cells, false, moore::<2>(), life_rule);
+        life.step();
+        assert!(*life.get(&[-1, 0]) && *life.get(&[1, 0]) && !*life.get(&[0, 1]));
+        life.step();
+        assert!(*life.get(&[0, 1]));
+        assert_eq!(life.len(), 3);
+    }
+
+    const TILES: &str = indoc! {"
+        sesenwnenenewseeswwswswwnenewsewsw
+        neeenesenwnwwswnenewnwwsewnenwseswesw
+        seswneswswsenwwnwse
+        nwnwneseeswswnenewneswwnewseswneseene
+        swweswneswnenwsewnwneneseenw
+        eesenwseswswnenwswnwnwsewwnwsene
+        sewnenenenesenwsewnenwwwse
+        wenwwweseeeweswwwnwwe
+        wsweesenenewnwwnwsenewsenwwsesesenwne
+        neeswseenwwswnwswswnw
+        nenwswwsewswnenenewsenwsenwnesesenew
+        enewnwewneswsewnwswenweswnenwsenwsw
+        sweneswneswneneenwnewenewwneswswnese
+        swwesenesewenwneswnwwneseswwne
+        enesenwswwswneneswsenwnewswseenwsese
+        wnwnesenesenenwwnenwsewesewsesesew
+        nenewswnwewswnenesenwnesewesw
+        eneswnwswnwsenenwnwnwwseeswneewsenese
+        neswnwewnwnwseenwseesewsenwsweewe
+        wseweeenwnesenwwwswnew
+    "};
+
+    // follows each line of directions from the centre, in axial coords
+    fn flipped_tiles(input: &str) -> HashSet<[i64; 2]> {
+        let mut black = HashSet::new();
+        for line in input.lines() {
+            let mut chars = line.chars();
+            let mut pos = [0, 0];
+            while let Some(c) = chars.next() {
+                let [dx, dy] = match (c, "ns".contains(c).then(|| chars.next().unwrap())) {
+                    ('e', None) => [1, 0],
+                    ('w', None) => [-1, 0],
+                    ('n', Some('e')) => [1, -1],
+                    ('n', Some('w')) => [0, -1],
+                    ('s', Some('e')) => [0, 1],
+                    ('s', Some('w')) => [-1, 1],
+                    other => panic!("Unexpected direction: {:?}", other),
+                };
+                pos = [pos[0] + dx, pos[1] + dy];
+            }
+            if !black.remove(&pos) {
+                black.insert(pos);
+            }
+        }
+        black
+    }
+
+    #[test]
+    fn test_hex_tiles() {
+        let black = flipped_tiles(TILES);
+        assert_eq!(black.len(), 10);
+
+        let cells = black.into_iter().map(|pos| (pos, true));
+        let mut tiles = SparseAutomaton::new(cells, false, HEX.to_vec(), |black, n| {
+            let count = n.iter().filter(|n| **n).count();
+            if *black {
+                count == 1 || count == 2
+            } else {
+                count == 2
+            }
+        });
+
+        let mut counts = vec![];
+        for _ in 0..10 {
+            tiles.step();
+            counts.push(tiles.len());
+        }
+        assert_eq!(counts, vec![15, 12, 25, 14, 23, 28, 41, 37, 49, 37]);
+    }
+}
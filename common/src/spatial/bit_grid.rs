@@ -0,0 +1,398 @@
+use super::Connectivity;
+
+/// A grid of booleans packed 64 to a word, so whole rows can be shifted,
+/// combined and counted a word at a time. Cell `(x, y)` is bit `x % 64` of
+/// word `x / 64` in row `y`, and bits past the width are always clear.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct BitGrid {
+    width: usize,
+    height: usize,
+    stride: usize,
+    words: Vec<u64>,
+}
+
+impl BitGrid {
+    pub fn new(width: usize, height: usize) -> Self {
+        let stride = width.div_ceil(64);
+        Self {
+            width,
+            height,
+            stride,
+            words: vec![0; stride * height],
+        }
+    }
+
+    pub fn parse<F>(input: &str, mut is_set: F) -> Self
+    where
+        F: FnMut(char) -> bool,
+    {
+        let lines: Vec<&str> = input.lines().collect();
+        let width = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+        let mut grid = Self::new(width, lines.len());
+        for (y, line) in lines.iter().enumerate() {
+            for (x, c) in line.chars().enumerate() {
+                if is_set(c) {
+                    grid.set(x, y, true);
+                }
+            }
+        }
+        grid
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        assert!(x < self.width && y < self.height, "Off the grid");
+        self.words[y * self.stride + x / 64] & (1 << (x % 64)) != 0
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, val: bool) {
+        assert!(x < self.width && y < self.height, "Off the grid");
+        let word = &mut self.words[y * self.stride + x / 64];
+        if val {
+            *word |= 1 << (x % 64);
+        } else {
+            *word &= !(1 << (x % 64));
+        }
+    }
+
+    pub fn row(&self, y: usize) -> &[u64] {
+        &self.words[y * self.stride..(y + 1) * self.stride]
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    // clears the unused bits at the end of each row
+    fn mask_rows(&mut self) {
+        let spare = self.stride * 64 - self.width;
+        if spare == 0 || self.stride == 0 {
+            return;
+        }
+        let mask = u64::MAX >> spare;
+        for row in self.words.chunks_mut(self.stride) {
+            *row.last_mut().unwrap() &= mask;
+        }
+    }
+
+    fn zip_with<F>(&self, other: &Self, f: F) -> Self
+    where
+        F: Fn(u64, u64) -> u64,
+    {
+        assert_eq!(
+            (self.width, self.height),
+            (other.width, other.height),
+            "Grids are different sizes"
+        );
+        let words = self
+            .words
+            .iter()
+            .zip(&other.words)
+            .map(|(a, b)| f(*a, *b))
+            .collect();
+        Self { words, ..*self }
+    }
+
+    pub fn and(&self, other: &Self) -> Self {
+        self.zip_with(other, |a, b| a & b)
+    }
+
+    pub fn or(&self, other: &Self) -> Self {
+        self.zip_with(other, |a, b| a | b)
+    }
+
+    pub fn xor(&self, other: &Self) -> Self {
+        self.zip_with(other, |a, b| a ^ b)
+    }
+
+    pub fn and_not(&self, other: &Self) -> Self {
+        self.zip_with(other, |a, b| a & !b)
+    }
+
+    pub fn not(&self) -> Self {
+        let mut out = Self {
+            words: self.words.iter().map(|w| !w).collect(),
+            ..*self
+        };
+        out.mask_rows();
+        out
+    }
+
+    /// Every cell moved one step east, with the east edge falling off.
+    pub fn shift_east(&self) -> Self {
+        let mut out = self.clone();
+        if self.stride == 0 {
+            return out;
+        }
+        for row in out.words.chunks_mut(self.stride) {
+            let mut carry = 0;
+            for word in row.iter_mut() {
+                let next_carry = *word >> 63;
+                *word = (*word << 1) | carry;
+                carry = next_carry;
+            }
+        }
+        out.mask_rows();
+        out
+    }
+
+    /// Every cell moved one step west, with the west edge falling off.
+    pub fn shift_west(&self) -> Self {
+        let mut out = self.clone();
+        if self.stride == 0 {
+            return out;
+        }
+        for row in out.words.chunks_mut(self.stride) {
+            let mut carry = 0;
+            for word in row.iter_mut().rev() {
+                let next_carry = *word << 63;
+                *word = (*word >> 1) | carry;
+                carry = next_carry;
+            }
+        }
+        out
+    }
+
+    /// Every cell moved one step north, with the top row falling off.
+    pub fn shift_north(&self) -> Self {
+        let mut out = Self::new(self.width, self.height);
+        if self.height > 0 {
+            out.words[..self.stride * (self.height - 1)]
+                .copy_from_slice(&self.words[self.stride..]);
+        }
+        out
+    }
+
+    /// Every cell moved one step south, with the bottom row falling off.
+    pub fn shift_south(&self) -> Self {
+        let mut out = Self::new(self.width, self.height);
+        if self.height > 0 {
+            out.words[self.stride..]
+                .copy_from_slice(&self.words[..self.stride * (self.height - 1)]);
+        }
+        out
+    }
+
+    /// Number of set neighbours of each cell, bit-sliced: bit `b` of a
+    /// cell's count lives in `planes[b]`. Built from shifted copies of the
+    /// grid fed through a ripple of half adders, 64 cells at a time.
+    pub fn neighbour_counts(&self, connectivity: Connectivity) -> [Self; 4] {
+        // each cell lined up with the neighbour on one side of it
+        let from_north = self.shift_south();
+        let from_south = self.shift_north();
+        let mut shifted = vec![self.shift_east(), self.shift_west()];
+        if connectivity == Connectivity::Eight {
+            shifted.extend([
+                from_north.shift_east(),
+                from_north.shift_west(),
+                from_south.shift_east(),
+                from_south.shift_west(),
+            ]);
+        }
+        shifted.extend([from_north, from_south]);
+
+        let mut planes: [Vec<u64>; 4] = Default::default();
+        for plane in planes.iter_mut() {
+            *plane = vec![0; self.words.len()];
+        }
+        for grid in shifted {
+            for (i, word) in grid.words.iter().enumerate() {
+                let mut carry = *word;
+                for plane in planes.iter_mut() {
+                    let sum = plane[i] ^ carry;
+                    carry &= plane[i];
+                    plane[i] = sum;
+                }
+            }
+        }
+
+        planes.map(|words| Self { words, ..*self })
+    }
+
+    /// Cells whose bit-sliced count is exactly `n`. Counts only go up to 8,
+    /// so anything higher matches nothing rather than wrapping round the
+    /// four planes.
+    pub fn count_equals(planes: &[Self; 4], n: u8) -> Self {
+        if n > 8 {
+            return Self::new(planes[0].width, planes[0].height);
+        }
+        let mut words = vec![u64::MAX; planes[0].words.len()];
+        for (b, plane) in planes.iter().enumerate() {
+            for (word, bits) in words.iter_mut().zip(&plane.words) {
+                *word &= if n & (1 << b) != 0 { *bits } else { !bits };
+            }
+        }
+        let mut out = Self { words, ..planes[0] };
+        out.mask_rows();
+        out
+    }
+
+    /// One generation of a life-like rule, with cells born when they have a
+    /// neighbour count in `born` and surviving with a count in `survive`.
+    /// Everything beyond the edges counts as unset.
+    pub fn life_step(&self, connectivity: Connectivity, born: &[u8], survive: &[u8]) -> Self {
+        let planes = self.neighbour_counts(connectivity);
+        let any = |counts: &[u8]| {
+            counts
+                .iter()
+                .map(|n| Self::count_equals(&planes, *n))
+                .fold(Self::new(self.width, self.height), |acc, g| acc.or(&g))
+        };
+        any(born).and_not(self).or(&any(survive).and(self))
+    }
+
+    pub fn render(&self) -> String {
+        let mut out = String::with_capacity((self.width + 1) * self.height);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                out.push(if self.get(x, y) { '#' } else { '.' });
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use indoc::indoc;
+
+    #[test]
+    fn test_get_set() {
+        let mut grid = BitGrid::new(130, 3);
+        grid.set(0, 0, true);
+        grid.set(64, 1, true);
+        grid.set(129, 2, true);
+        assert!(grid.get(64, 1) && !grid.get(63, 1));
+        assert_eq!(grid.count_ones(), 3);
+        assert_eq!(grid.row(1), &[0, 1, 0]);
+        assert_eq!(grid.not().count_ones(), 130 * 3 - 3);
+
+        grid.set(64, 1, false);
+        assert_eq!(grid.count_ones(), 2);
+    }
+
+    #[test]
+    fn test_shifts() {
+        let mut grid = BitGrid::new(130, 2);
+        grid.set(63, 0, true);
+        grid.set(129, 0, true);
+        grid.set(64, 1, true);
+
+        let east = grid.shift_east();
+        assert!(east.get(64, 0) && east.get(65, 1));
+        assert_eq!(east.count_ones(), 2);
+
+        let west = grid.shift_west();
+        assert!(west.get(62, 0) && west.get(128, 0) && west.get(63, 1));
+
+        let north = grid.shift_north();
+        assert_eq!(north.count_ones(), 1);
+        assert!(north.get(64, 0));
+
+        let south = grid.shift_south();
+        assert!(south.get(63, 1) && south.get(129, 1));
+
+        let empty = BitGrid::new(0, 3);
+        for shifted in [
+            empty.shift_east(),
+            empty.shift_west(),
+            empty.shift_north(),
+            empty.shift_south(),
+        ] {
+            assert_eq!(shifted, empty);
+        }
+    }
+
+    #[test]
+    fn test_neighbour_counts() {
+        let grid = BitGrid::parse(
+            indoc! {"
+                ###
+                #.#
+                ###
+            "},
+            |c| c == '#',
+        );
+        let planes = grid.neighbour_counts(Connectivity::Eight);
+        assert!(BitGrid::count_equals(&planes, 8).get(1, 1));
+        assert!(BitGrid::count_equals(&planes, 2).get(0, 0));
+        assert!(BitGrid::count_equals(&planes, 4).get(1, 0));
+
+        let planes = grid.neighbour_counts(Connectivity::Four);
+        assert_eq!(BitGrid::count_equals(&planes, 4).count_ones(), 1);
+        assert_eq!(BitGrid::count_equals(&planes, 0).count_ones(), 0);
+
+        // 16 has the same low four bits as 0, so would otherwise match
+        // every cell with no neighbours
+        let planes = BitGrid::new(3, 3).neighbour_counts(Connectivity::Eight);
+        assert_eq!(BitGrid::count_equals(&planes, 0).count_ones(), 9);
+        for n in [9, 16, 24, u8::MAX] {
+            assert_eq!(BitGrid::count_equals(&planes, n).count_ones(), 0);
+        }
+    }
+
+    // the same rule one cell at a time, for comparison
+    fn naive_life(grid: &BitGrid) -> BitGrid {
+        let mut out = BitGrid::new(grid.width(), grid.height());
+        for y in 0..grid.height() {
+            for x in 0..grid.width() {
+                let mut count = 0;
+                for ny in y.saturating_sub(1)..=(y + 1).min(grid.height() - 1) {
+                    for nx in x.saturating_sub(1)..=(x + 1).min(grid.width() - 1) {
+                        if (nx, ny) != (x, y) && grid.get(nx, ny) {
+                            count += 1;
+                        }
+                    }
+                }
+                out.set(x, y, count == 3 || (count == 2 && grid.get(x, y)));
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn test_life() {
+        let glider = indoc! {"
+            .#......
+            ..#.....
+            ###.....
+            ........
+            ........
+        "};
+        let mut grid = BitGrid::parse(glider, |c| c == '#');
+        for _ in 0..4 {
+            grid = grid.life_step(Connectivity::Eight, &[3], &[2, 3]);
+        }
+        assert_eq!(
+            grid.render(),
+            indoc! {"
+                ........
+                ..#.....
+                ...#....
+                .###....
+                ........
+            "}
+        );
+
+        // a wide pattern, crossing word boundaries
+        let mut wide = BitGrid::new(150, 20);
+        for i in 0..600 {
+            wide.set((i * 37) % 150, (i * 11) % 20, true);
+        }
+        for _ in 0..10 {
+            let expected = naive_life(&wide);
+            wide = wide.life_step(Connectivity::Eight, &[3], &[2, 3]);
+            assert_eq!(wide, expected);
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use super::interval::Interval;
+
+/// An axis-aligned box in `N` dimensions, inclusive on every side.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Cuboid<const N: usize> {
+    pub ranges: [Interval<i64>; N],
+}
+
+impl<const N: usize> Cuboid<N> {
+    pub fn new(ranges: [Interval<i64>; N]) -> Self {
+        Self { ranges }
+    }
+
+    /// The same range on every axis.
+    pub fn cube(start: i64, end: i64) -> Self {
+        Self::new([Interval::new(start, end); N])
+    }
+
+    pub fn volume(&self) -> u128 {
+        self.ranges.iter().map(|r| r.size()).product()
+    }
+
+    pub fn contains(&self, point: &[i64; N]) -> bool {
+        self.ranges.iter().zip(point).all(|(r, p)| r.contains(*p))
+    }
+
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let mut ranges = self.ranges;
+        for (range, other) in ranges.iter_mut().zip(&other.ranges) {
+            *range = range.intersection(other)?;
+        }
+        Some(Self { ranges })
+    }
+
+    /// Everything in this box that isn't in `other`, as up to `2 * N`
+    /// disjoint boxes. Each axis in turn has the slabs below and above
+    /// `other` cut off, leaving the overlap behind.
+    pub fn subtract(&self, other: &Self) -> Vec<Self> {
+        let overlap = match self.intersection(other) {
+            Some(overlap) => overlap,
+            None => return vec![*self],
+        };
+
+        let mut out = vec![];
+        let mut rest = *self;
+        for axis in 0..N {
+            let range = rest.ranges[axis];
+            let keep = overlap.ranges[axis];
+
+            if range.start < keep.start {
+                let mut below = rest;
+                below.ranges[axis] = Interval::new(range.start, keep.start - 1);
+                out.push(below);
+            }
+            if keep.end < range.end {
+                let mut above = rest;
+                above.ranges[axis] = Interval::new(keep.end + 1, range.end);
+                out.push(above);
+            }
+            rest.ranges[axis] = keep;
+        }
+        out
+    }
+}
+
+/// A region of space made up of disjoint boxes, so that its volume is just
+/// the sum of theirs.
+#[derive(Clone, Debug, Default)]
+pub struct BoxSet<const N: usize> {
+    boxes: Vec<Cuboid<N>>,
+}
+
+impl<const N: usize> BoxSet<N> {
+    pub fn new() -> Self {
+        Self { boxes: vec![] }
+    }
+
+    pub fn boxes(&self) -> &[Cuboid<N>] {
+        &self.boxes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.boxes.is_empty()
+    }
+
+    pub fn volume(&self) -> u128 {
+        self.boxes.iter().map(|b| b.volume()).sum()
+    }
+
+    pub fn contains(&self, point: &[i64; N]) -> bool {
+        self.boxes.iter().any(|b| b.contains(point))
+    }
+
+    pub fn add(&mut self, cuboid: Cuboid<N>) {
+        self.remove(&cuboid);
+        self.boxes.push(cuboid);
+    }
+
+    pub fn remove(&mut self, cuboid: &Cuboid<N>) {
+        self.boxes = self.boxes.iter().flat_map(|b| b.subtract(cuboid)).collect();
+    }
+
+    /// Only the parts inside `region`.
+    pub fn clip(&mut self, region: &Cuboid<N>) {
+        self.boxes = self
+            .boxes
+            .iter()
+            .filter_map(|b| b.intersection(region))
+            .collect();
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        // both sides are disjoint, so every pairwise overlap is too
+        let boxes = self
+            .boxes
+            .iter()
+            .flat_map(|a| other.boxes.iter().filter_map(move |b| a.intersection(b)))
+            .collect();
+        Self { boxes }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cuboid<const N: usize>(ranges: [(i64, i64); N]) -> Cuboid<N> {
+        Cuboid::new(ranges.map(|(start, end)| Interval::new(start, end)))
+    }
+
+    #[test]
+    fn test_subtract() {
+        let outer = Cuboid::<3>::cube(0, 9);
+        let inner = Cuboid::cube(3, 5);
+        let pieces = outer.subtract(&inner);
+        assert_eq!(pieces.len(), 6);
+        assert_eq!(pieces.iter().map(|p| p.volume()).sum::<u128>(), 1000 - 27);
+        assert!(pieces.iter().all(|p| p.intersection(&inner).is_none()));
+
+        // only overlapping on one side
+        let corner = cuboid([(8, 20), (-5, 20), (-5, 20)]);
+        assert_eq!(
+            outer.subtract(&corner),
+            vec![cuboid([(0, 7), (0, 9), (0, 9)])]
+        );
+
+        let far = Cuboid::cube(20, 30);
+        assert_eq!(outer.subtract(&far), vec![outer]);
+        assert!(inner.subtract(&outer).is_empty());
+    }
+
+    #[test]
+    fn test_reactor_reboot() {
+        let steps = [
+            (true, cuboid([(10, 12), (10, 12), (10, 12)])),
+            (true, cuboid([(11, 13), (11, 13), (11, 13)])),
+            (false, cuboid([(9, 11), (9, 11), (9, 11)])),
+            (true, cuboid([(10, 10), (10, 10), (10, 10)])),
+        ];
+
+        let mut reactor = BoxSet::new();
+        let mut volumes = vec![];
+        for (on, step) in steps {
+            if on {
+                reactor.add(step);
+            } else {
+                reactor.remove(&step);
+            }
+            volumes.push(reactor.volume());
+        }
+        assert_eq!(volumes, vec![27, 46, 38, 39]);
+        assert!(reactor.contains(&[10, 10, 10]));
+        assert!(!reactor.contains(&[11, 11, 11]));
+
+        reactor.clip(&Cuboid::cube(12, 50));
+        assert_eq!(reactor.volume(), 8);
+    }
+
+    #[test]
+    fn test_other_dimensions() {
+        let mut rects = BoxSet::new();
+        rects.add(cuboid([(0, 3), (0, 3)]));
+        rects.add(cuboid([(2, 5), (2, 5)]));
+        assert_eq!(rects.volume(), 16 + 16 - 4);
+
+        let mut window = BoxSet::new();
+        window.add(cuboid([(3, 10), (0, 10)]));
+        assert_eq!(rects.intersection(&window).volume(), 14);
+
+        let mut hyper = BoxSet::new();
+        hyper.add(Cuboid::<4>::cube(0, 1_000_000));
+        hyper.remove(&Cuboid::cube(1, 1_000_000));
+        assert_eq!(hyper.volume(), 1_000_001u128.pow(4) - 1_000_000u128.pow(4));
+    }
+}
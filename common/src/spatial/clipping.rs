@@ -0,0 +1,331 @@
+use num::{One, Signed, Zero};
+
+use super::Coord;
+use crate::math::Rational;
+
+pub type Point = Coord<Rational>;
+
+pub fn point(x: i128, y: i128) -> Point {
+    Coord {
+        x: Rational::from_integer(x),
+        y: Rational::from_integer(y),
+    }
+}
+
+/// The closed half-plane `a*x + b*y <= c`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct HalfPlane {
+    pub a: Rational,
+    pub b: Rational,
+    pub c: Rational,
+}
+
+impl HalfPlane {
+    pub fn new(a: Rational, b: Rational, c: Rational) -> Self {
+        assert!(
+            !a.is_zero() || !b.is_zero(),
+            "Half-plane needs a non-zero normal"
+        );
+        Self { a, b, c }
+    }
+
+    /// Bounded by the line through `p` and `q`, on the same side as `inside`.
+    pub fn through(p: &Point, q: &Point, inside: &Point) -> Self {
+        // normal perpendicular to the line, pointing out of the half-plane
+        let a = q.y - p.y;
+        let b = p.x - q.x;
+        let out = Self::new(a, b, a * p.x + b * p.y);
+        if out.contains(inside) {
+            out
+        } else {
+            out.complement()
+        }
+    }
+
+    /// The opposite side, sharing the boundary line.
+    pub fn complement(&self) -> Self {
+        Self::new(-self.a, -self.b, -self.c)
+    }
+
+    // negative inside, zero on the boundary, positive outside
+    fn value(&self, p: &Point) -> Rational {
+        self.a * p.x + self.b * p.y - self.c
+    }
+
+    pub fn contains(&self, p: &Point) -> bool {
+        !self.value(p).is_positive()
+    }
+}
+
+/// A convex polygon with exact rational vertices. Clipping always produces
+/// convex pieces, so these are closed under all the operations here.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConvexPolygon {
+    vertices: Vec<Point>,
+}
+
+impl ConvexPolygon {
+    /// Vertices must already be in order (either direction) around a convex
+    /// shape. Returns `None` if they don't enclose any area.
+    pub fn new(vertices: Vec<Point>) -> Option<Self> {
+        let mut vertices = vertices;
+        vertices.dedup();
+        while vertices.len() > 1 && vertices.first() == vertices.last() {
+            vertices.pop();
+        }
+
+        let polygon = Self { vertices };
+        if polygon.vertices.len() >= 3 && !polygon.double_signed_area().is_zero() {
+            Some(polygon)
+        } else {
+            None
+        }
+    }
+
+    pub fn rectangle(left: Rational, right: Rational, top: Rational, bottom: Rational) -> Self {
+        Self::new(vec![
+            Coord { x: left, y: top },
+            Coord { x: right, y: top },
+            Coord {
+                x: right,
+                y: bottom,
+            },
+            Coord { x: left, y: bottom },
+        ])
+        .expect("Rectangle has no area")
+    }
+
+    /// Every point within Manhattan distance `radius` of `centre`.
+    pub fn diamond(centre: &Point, radius: Rational) -> Self {
+        let (x, y) = (centre.x, centre.y);
+        Self::new(vec![
+            Coord { x, y: y - radius },
+            Coord { x: x + radius, y },
+            Coord { x, y: y + radius },
+            Coord { x: x - radius, y },
+        ])
+        .expect("Diamond has no area")
+    }
+
+    pub fn vertices(&self) -> &[Point] {
+        &self.vertices
+    }
+
+    fn edges(&self) -> impl Iterator<Item = (&Point, &Point)> {
+        self.vertices
+            .iter()
+            .zip(self.vertices.iter().cycle().skip(1))
+    }
+
+    fn double_signed_area(&self) -> Rational {
+        self.edges().map(|(a, b)| a.x * b.y - b.x * a.y).sum()
+    }
+
+    pub fn area(&self) -> Rational {
+        self.double_signed_area().abs() / 2
+    }
+
+    // the vertex average is always strictly inside a convex polygon
+    fn interior_point(&self) -> Point {
+        let n = Rational::from_integer(self.vertices.len() as i128);
+        let sum = self.vertices.iter().fold(point(0, 0), |acc, p| Coord {
+            x: acc.x + p.x,
+            y: acc.y + p.y,
+        });
+        Coord {
+            x: sum.x / n,
+            y: sum.y / n,
+        }
+    }
+
+    /// The half-planes whose intersection is this polygon.
+    pub fn half_planes(&self) -> Vec<HalfPlane> {
+        let inside = self.interior_point();
+        self.edges()
+            .map(|(p, q)| HalfPlane::through(p, q, &inside))
+            .collect()
+    }
+
+    /// Includes points on the boundary.
+    pub fn contains(&self, p: &Point) -> bool {
+        self.half_planes().iter().all(|h| h.contains(p))
+    }
+
+    /// The part of this polygon inside the half-plane, if any.
+    pub fn clip(&self, half_plane: &HalfPlane) -> Option<Self> {
+        let mut out = vec![];
+        for (p, q) in self.edges() {
+            let vp = half_plane.value(p);
+            let vq = half_plane.value(q);
+            if !vp.is_positive() {
+                out.push(*p);
+            }
+            if (vp.is_negative() && vq.is_positive()) || (vp.is_positive() && vq.is_negative()) {
+                // edge crosses the boundary line
+                let t = vp / (vp - vq);
+                out.push(Coord {
+                    x: p.x + t * (q.x - p.x),
+                    y: p.y + t * (q.y - p.y),
+                });
+            }
+        }
+        Self::new(out)
+    }
+
+    /// Splits into the parts `(outside, inside)` of the half-plane. Pieces
+    /// with no area are dropped.
+    pub fn bisect(&self, half_plane: &HalfPlane) -> (Option<Self>, Option<Self>) {
+        (self.clip(&half_plane.complement()), self.clip(half_plane))
+    }
+
+    /// Everything in this polygon that isn't in `other`, as non-overlapping
+    /// convex pieces.
+    pub fn subtract(&self, other: &Self) -> Vec<Self> {
+        let mut out = vec![];
+        let mut remaining = self.clone();
+        for half_plane in other.half_planes() {
+            let (outside, inside) = remaining.bisect(&half_plane);
+            out.extend(outside);
+            match inside {
+                Some(polygon) => remaining = polygon,
+                // no overlap, so don't bother splitting this up
+                None => return vec![self.clone()],
+            }
+        }
+        out
+    }
+
+    /// Integer points inside or on the boundary. Scans the bounding box, so
+    /// only use this on small polygons.
+    pub fn lattice_points(&self) -> Vec<Coord<i128>> {
+        let min_x = self.vertices.iter().map(|p| p.x).min().unwrap().ceil();
+        let max_x = self.vertices.iter().map(|p| p.x).max().unwrap().floor();
+        let min_y = self.vertices.iter().map(|p| p.y).min().unwrap().ceil();
+        let max_y = self.vertices.iter().map(|p| p.y).max().unwrap().floor();
+
+        let half_planes = self.half_planes();
+        let mut out = vec![];
+        let mut y = min_y;
+        while y <= max_y {
+            let mut x = min_x;
+            while x <= max_x {
+                let p = Coord { x, y };
+                if half_planes.iter().all(|h| h.contains(&p)) {
+                    out.push(Coord {
+                        x: x.to_integer(),
+                        y: y.to_integer(),
+                    });
+                }
+                x += Rational::one();
+            }
+            y += Rational::one();
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn r(n: i128) -> Rational {
+        Rational::from_integer(n)
+    }
+
+    #[test]
+    fn test_area_and_contains() {
+        let square = ConvexPolygon::rectangle(r(0), r(4), r(0), r(4));
+        assert_eq!(square.area(), r(16));
+        assert!(square.contains(&point(2, 2)));
+        assert!(square.contains(&point(4, 0)));
+        assert!(!square.contains(&point(5, 2)));
+
+        let diamond = ConvexPolygon::diamond(&point(0, 0), r(2));
+        assert_eq!(diamond.area(), r(8));
+        assert!(diamond.contains(&point(1, -1)));
+        assert!(!diamond.contains(&point(2, 1)));
+
+        let flat = ConvexPolygon::new(vec![point(0, 0), point(1, 1), point(2, 2)]);
+        assert_eq!(flat, None);
+    }
+
+    #[test]
+    fn test_bisect() {
+        let square = ConvexPolygon::rectangle(r(0), r(4), r(0), r(4));
+
+        // x + y <= 2 cuts off the top-left corner
+        let half_plane = HalfPlane::new(r(1), r(1), r(2));
+        let (outside, inside) = square.bisect(&half_plane);
+        assert_eq!(inside.unwrap().area(), r(2));
+        assert_eq!(outside.unwrap().area(), r(14));
+
+        // a half-plane that misses the square entirely
+        let half_plane = HalfPlane::new(r(1), r(0), r(-1));
+        let (outside, inside) = square.bisect(&half_plane);
+        assert_eq!(outside.unwrap(), square);
+        assert_eq!(inside, None);
+
+        // cuts along an existing edge
+        let half_plane = HalfPlane::new(r(0), r(1), r(4));
+        let (outside, inside) = square.bisect(&half_plane);
+        assert_eq!(outside, None);
+        assert_eq!(inside.unwrap(), square);
+    }
+
+    #[test]
+    fn test_subtract() {
+        let square = ConvexPolygon::rectangle(r(0), r(4), r(0), r(4));
+        let diamond = ConvexPolygon::diamond(&point(2, 2), r(2));
+        let pieces = square.subtract(&diamond);
+        assert_eq!(pieces.len(), 4);
+        assert!(pieces.iter().all(|p| p.area() == r(2)));
+
+        // subtracting something disjoint leaves it whole
+        let far = ConvexPolygon::diamond(&point(10, 10), r(1));
+        assert_eq!(square.subtract(&far), vec![square.clone()]);
+
+        // subtracting something covering it leaves nothing
+        let big = ConvexPolygon::diamond(&point(2, 2), r(5));
+        assert!(square.subtract(&big).is_empty());
+    }
+
+    #[test]
+    fn test_sensor_coverage() {
+        // the beacon exclusion zone example: sensors and their beacon distances
+        let sensors = [
+            ((2, 18), 7),
+            ((9, 16), 1),
+            ((13, 2), 3),
+            ((12, 14), 4),
+            ((10, 20), 4),
+            ((14, 17), 5),
+            ((8, 7), 9),
+            ((2, 0), 10),
+            ((0, 11), 3),
+            ((20, 14), 8),
+            ((17, 20), 6),
+            ((16, 7), 5),
+            ((14, 3), 1),
+            ((20, 1), 7),
+        ];
+
+        // pad everything by half a unit, so no lattice point lies on an edge
+        let half = Rational::new(1, 2);
+        let area = ConvexPolygon::rectangle(r(0) - half, r(20) + half, r(0) - half, r(20) + half);
+
+        let mut remaining = vec![area];
+        for ((x, y), distance) in sensors {
+            let covered = ConvexPolygon::diamond(&point(x, y), r(distance) + half);
+            remaining = remaining
+                .iter()
+                .flat_map(|polygon| polygon.subtract(&covered))
+                .collect();
+        }
+
+        let uncovered: Vec<Coord<i128>> = remaining
+            .iter()
+            .flat_map(|polygon| polygon.lattice_points())
+            .collect();
+        assert_eq!(uncovered, vec![Coord { x: 14, y: 11 }]);
+    }
+}
@@ -0,0 +1,206 @@
+use std::{
+    collections::BTreeSet,
+    fmt::Debug,
+    ops::{AddAssign, Not, Range},
+    str::FromStr,
+};
+
+use num::PrimInt;
+
+use super::Bounds;
+
+/// A grid over a huge coordinate space, where only the edges of a known set
+/// of rectangles matter. Each axis is split at every rectangle edge, so every
+/// compressed cell stands for a block of real cells that always share a value,
+/// weighted by the number of real cells it covers.
+#[derive(Clone, Debug)]
+pub struct CompressedGrid<T, V> {
+    x_splits: Vec<T>,
+    y_splits: Vec<T>,
+    width: usize,
+    sizes: Vec<usize>,
+    values: Vec<V>,
+}
+
+impl<T, V> CompressedGrid<T, V>
+where
+    T: FromStr + PrimInt,
+    <T as FromStr>::Err: Debug,
+{
+    /// Only rectangles in `rects` (or unions of their cells) can be
+    /// updated or queried afterwards. None of them can reach the maximum
+    /// value of `T`, as the split just after each one has to fit in `T`.
+    pub fn new(rects: &[Bounds<T>], initial: V) -> Self
+    where
+        V: Clone,
+    {
+        let x_splits = Self::splits(rects.iter().map(|r| (r.left, r.right)));
+        let y_splits = Self::splits(rects.iter().map(|r| (r.top, r.bottom)));
+
+        let x_sizes = Self::sizes(&x_splits);
+        let y_sizes = Self::sizes(&y_splits);
+
+        let sizes: Vec<usize> = y_sizes
+            .iter()
+            .flat_map(|y| x_sizes.iter().map(move |x| x * y))
+            .collect();
+        let values = vec![initial; sizes.len()];
+
+        Self {
+            x_splits,
+            y_splits,
+            width: x_sizes.len(),
+            sizes,
+            values,
+        }
+    }
+
+    // each inclusive range contributes its start, and the cell just after its end
+    fn splits<I>(ranges: I) -> Vec<T>
+    where
+        I: Iterator<Item = (T, T)>,
+    {
+        ranges
+            .fold(BTreeSet::new(), |mut set, (start, end)| {
+                set.insert(start);
+                set.insert(
+                    end.checked_add(&T::one())
+                        .expect("Rectangle reaches the maximum coordinate"),
+                );
+                set
+            })
+            .into_iter()
+            .collect()
+    }
+
+    fn sizes(splits: &[T]) -> Vec<usize> {
+        splits
+            .windows(2)
+            .map(|w| (w[1] - w[0]).to_usize().unwrap())
+            .collect()
+    }
+
+    fn range(splits: &[T], start: T, end: T) -> Range<usize> {
+        let find = |val: T| {
+            splits
+                .binary_search(&val)
+                .unwrap_or_else(|_| panic!("Rectangle edge isn't on a split: {:?}", val.to_i128()))
+        };
+        find(start)..find(end + T::one())
+    }
+
+    fn indices(&self, rect: &Bounds<T>) -> impl Iterator<Item = usize> + '_ {
+        let x_range = Self::range(&self.x_splits, rect.left, rect.right);
+        let y_range = Self::range(&self.y_splits, rect.top, rect.bottom);
+        y_range.flat_map(move |yi| x_range.clone().map(move |xi| yi * self.width + xi))
+    }
+
+    pub fn update<F>(&mut self, rect: &Bounds<T>, mut f: F)
+    where
+        F: FnMut(&V) -> V,
+    {
+        let indices: Vec<usize> = self.indices(rect).collect();
+        for i in indices {
+            self.values[i] = f(&self.values[i]);
+        }
+    }
+
+    pub fn set(&mut self, rect: &Bounds<T>, val: V)
+    where
+        V: Clone,
+    {
+        self.update(rect, |_| val.clone());
+    }
+
+    pub fn add(&mut self, rect: &Bounds<T>, delta: V)
+    where
+        V: AddAssign + Clone,
+    {
+        self.update(rect, |v| {
+            let mut out = v.clone();
+            out += delta.clone();
+            out
+        });
+    }
+
+    pub fn toggle(&mut self, rect: &Bounds<T>)
+    where
+        V: Not<Output = V> + Clone,
+    {
+        self.update(rect, |v| !v.clone());
+    }
+
+    pub fn values_in(&self, rect: &Bounds<T>) -> impl Iterator<Item = &V> + '_ {
+        self.indices(rect).map(|i| &self.values[i])
+    }
+
+    /// Sum of `f` over every real cell.
+    pub fn weighted_sum<F>(&self, f: F) -> usize
+    where
+        F: Fn(&V) -> usize,
+    {
+        self.values
+            .iter()
+            .zip(&self.sizes)
+            .map(|(v, size)| size * f(v))
+            .sum()
+    }
+
+    /// Number of real cells where `f` holds.
+    pub fn area_where<F>(&self, f: F) -> usize
+    where
+        F: Fn(&V) -> bool,
+    {
+        self.weighted_sum(|v| if f(v) { 1 } else { 0 })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_and_toggle() {
+        let rects: [Bounds<u32>; 3] = [
+            Bounds::new(0, 999, 0, 999),
+            Bounds::new(0, 999, 0, 0),
+            Bounds::new(499, 500, 499, 500),
+        ];
+        let mut grid = CompressedGrid::new(&rects, false);
+        grid.set(&rects[0], true);
+        grid.toggle(&rects[1]);
+        grid.set(&rects[2], false);
+        assert_eq!(grid.area_where(|on| *on), 1000000 - 1000 - 4);
+        assert_eq!(grid.values_in(&rects[2]).count(), 1);
+    }
+
+    #[test]
+    fn test_add() {
+        let rects: [Bounds<i64>; 3] = [
+            Bounds::new(1, 4, 3, 6),
+            Bounds::new(3, 6, 1, 4),
+            Bounds::new(5, 6, 5, 6),
+        ];
+        let mut grid = CompressedGrid::new(&rects, 0u8);
+        for rect in &rects {
+            grid.add(rect, 1);
+        }
+        assert_eq!(grid.area_where(|claims| *claims >= 2), 4);
+        assert_eq!(grid.weighted_sum(|claims| *claims as usize), 16 + 16 + 4);
+        assert!(grid.values_in(&rects[2]).all(|claims| *claims == 1));
+        assert!(!grid.values_in(&rects[0]).all(|claims| *claims == 1));
+    }
+
+    #[test]
+    #[should_panic(expected = "Rectangle edge isn't on a split")]
+    fn test_unknown_rect() {
+        let mut grid = CompressedGrid::new(&[Bounds::new(0u8, 9, 0, 9)], false);
+        grid.set(&Bounds::new(0, 4, 0, 9), true);
+    }
+
+    #[test]
+    #[should_panic(expected = "Rectangle reaches the maximum coordinate")]
+    fn test_rect_at_max() {
+        CompressedGrid::new(&[Bounds::new(0u8, 9, 250, 255)], false);
+    }
+}
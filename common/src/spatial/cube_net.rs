@@ -0,0 +1,350 @@
+use std::collections::{HashSet, VecDeque};
+
+use super::{Coord, Direction};
+
+type Vec3 = [i64; 3];
+
+const DIRECTIONS: [Direction; 4] = [
+    Direction::North,
+    Direction::East,
+    Direction::South,
+    Direction::West,
+];
+
+// where a face of the net ends up once folded, as unit vectors
+#[derive(Clone, Copy, Debug)]
+struct Face {
+    origin: Coord<i64>,
+    normal: Vec3,
+    right: Vec3,
+    down: Vec3,
+}
+
+impl Face {
+    // which way a direction on the net points, once folded
+    fn vector(&self, direction: &Direction) -> Vec3 {
+        match direction {
+            Direction::North => neg(self.down),
+            Direction::South => self.down,
+            Direction::West => neg(self.right),
+            Direction::East => self.right,
+        }
+    }
+
+    // rolls the cube over the given edge, to place the face beyond it
+    fn neighbour(&self, direction: &Direction, origin: Coord<i64>) -> Self {
+        let (normal, right, down) = match direction {
+            Direction::North => (neg(self.down), self.right, self.normal),
+            Direction::South => (self.down, self.right, neg(self.normal)),
+            Direction::West => (neg(self.right), self.normal, self.down),
+            Direction::East => (self.right, neg(self.normal), self.down),
+        };
+        Self {
+            origin,
+            normal,
+            right,
+            down,
+        }
+    }
+
+    fn direction_of(&self, vector: Vec3) -> Direction {
+        *DIRECTIONS
+            .iter()
+            .find(|d| self.vector(d) == vector)
+            .unwrap()
+    }
+}
+
+fn neg(v: Vec3) -> Vec3 {
+    v.map(|x| -x)
+}
+
+fn dot(a: Vec3, b: Vec3) -> i64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+/// Where walking off one side of a face comes back onto the net.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Edge {
+    pub face: usize,
+    pub side: Direction,
+    /// Clockwise quarter turns applied to the facing when crossing over.
+    pub turns: usize,
+}
+
+/// A flat map made of six square faces, folded up into a cube so that
+/// walking off the edge of one face continues onto whichever face it's
+/// glued to. Works for any of the 11 nets, at any face size.
+#[derive(Clone, Debug)]
+pub struct CubeNet {
+    size: i64,
+    faces: Vec<Face>,
+}
+
+impl CubeNet {
+    /// Faces are given by their position in units of whole faces, so a
+    /// face at `(2, 1)` covers cells `2*size..3*size` across.
+    pub fn new(faces: &[Coord<i64>], size: i64) -> Self {
+        assert_eq!(faces.len(), 6, "Not a cube net");
+
+        let first = Face {
+            origin: faces[0] * size,
+            normal: [0, 0, -1],
+            right: [1, 0, 0],
+            down: [0, 1, 0],
+        };
+        let mut placed: Vec<Option<Face>> = vec![None; 6];
+        placed[0] = Some(first);
+
+        let mut queue = VecDeque::from([first]);
+        while let Some(face) = queue.pop_front() {
+            let position = face.origin / size;
+            for direction in &DIRECTIONS {
+                let next = position.shift(direction);
+                if let Some(i) = faces.iter().position(|f| *f == next) {
+                    if placed[i].is_none() {
+                        let neighbour = face.neighbour(direction, next * size);
+                        placed[i] = Some(neighbour);
+                        queue.push_back(neighbour);
+                    }
+                }
+            }
+        }
+
+        let faces: Vec<Face> = placed
+            .into_iter()
+            .map(|f| f.expect("Not a cube net"))
+            .collect();
+        let normals: HashSet<Vec3> = faces.iter().map(|f| f.normal).collect();
+        assert_eq!(normals.len(), 6, "Not a cube net");
+
+        Self { size, faces }
+    }
+
+    /// Works out the face size and layout from every cell on the map, which
+    /// must be aligned so the faces start at multiples of their size.
+    pub fn from_cells<I>(cells: I) -> Self
+    where
+        I: IntoIterator<Item = Coord<i64>>,
+    {
+        let cells: Vec<Coord<i64>> = cells.into_iter().collect();
+        let size = ((cells.len() / 6) as f64).sqrt() as i64;
+        assert_eq!(size * size * 6, cells.len() as i64, "Not a cube net");
+
+        let mut faces: Vec<Coord<i64>> = vec![];
+        for cell in cells {
+            let face = Coord::new(cell.x.div_euclid(size), cell.y.div_euclid(size));
+            if !faces.contains(&face) {
+                faces.push(face);
+            }
+        }
+        faces.sort_by_key(|f| (f.y, f.x));
+        Self::new(&faces, size)
+    }
+
+    pub fn size(&self) -> i64 {
+        self.size
+    }
+
+    /// Top-left cell of each face.
+    pub fn face_origins(&self) -> impl Iterator<Item = Coord<i64>> + '_ {
+        self.faces.iter().map(|f| f.origin)
+    }
+
+    pub fn face_of(&self, coord: &Coord<i64>) -> Option<usize> {
+        self.faces.iter().position(|f| {
+            (f.origin.x..f.origin.x + self.size).contains(&coord.x)
+                && (f.origin.y..f.origin.y + self.size).contains(&coord.y)
+        })
+    }
+
+    pub fn contains(&self, coord: &Coord<i64>) -> bool {
+        self.face_of(coord).is_some()
+    }
+
+    /// The side of another face glued to the given side of `face`.
+    pub fn edge(&self, face: usize, direction: Direction) -> Edge {
+        let from = &self.faces[face];
+        let normal = from.vector(&direction);
+        let to = self.faces.iter().position(|f| f.normal == normal).unwrap();
+        let side = self.faces[to].direction_of(from.normal);
+
+        let arriving = side.opposite();
+        let mut turns = 0;
+        let mut facing = direction;
+        while facing != arriving {
+            facing = facing.clockwise();
+            turns += 1;
+        }
+
+        Edge {
+            face: to,
+            side,
+            turns,
+        }
+    }
+
+    /// Moves one cell forwards, wrapping around the cube if that goes off
+    /// the current face onto empty space.
+    pub fn step(&self, coord: &Coord<i64>, facing: Direction) -> (Coord<i64>, Direction) {
+        let next = coord.shift(&facing);
+        if self.contains(&next) {
+            return (next, facing);
+        }
+
+        let from = &self.faces[self.face_of(coord).expect("Not on the net")];
+        let n = self.size;
+
+        // position on the folded cube, doubled so cell centres are integers
+        let local = *coord - from.origin;
+        let mut point = [0; 3];
+        for (i, p) in point.iter_mut().enumerate() {
+            *p = from.normal[i] * n
+                + from.right[i] * (2 * local.x + 1 - n)
+                + from.down[i] * (2 * local.y + 1 - n);
+        }
+
+        // over the edge, and down onto the next face
+        let vector = from.vector(&facing);
+        for (i, p) in point.iter_mut().enumerate() {
+            *p += vector[i] - from.normal[i];
+        }
+
+        let to = self.faces.iter().find(|f| f.normal == vector).unwrap();
+        let x = (dot(point, to.right) + n - 1) / 2;
+        let y = (dot(point, to.down) + n - 1) / 2;
+        let facing = to.direction_of(neg(from.normal));
+
+        (to.origin + Coord::new(x, y), facing)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use indoc::indoc;
+
+    // every cube net, as face positions
+    const NETS: [[(i64, i64); 6]; 11] = [
+        [(0, 0), (0, 1), (1, 1), (2, 1), (3, 1), (0, 2)],
+        [(0, 0), (0, 1), (1, 1), (2, 1), (3, 1), (1, 2)],
+        [(0, 0), (0, 1), (1, 1), (2, 1), (3, 1), (2, 2)],
+        [(0, 0), (0, 1), (1, 1), (2, 1), (3, 1), (3, 2)],
+        [(1, 0), (0, 1), (1, 1), (2, 1), (3, 1), (1, 2)],
+        [(1, 0), (0, 1), (1, 1), (2, 1), (3, 1), (2, 2)],
+        [(0, 0), (1, 0), (1, 1), (2, 1), (3, 1), (1, 2)],
+        [(0, 0), (1, 0), (1, 1), (2, 1), (3, 1), (2, 2)],
+        [(0, 0), (1, 0), (1, 1), (2, 1), (3, 1), (3, 2)],
+        [(0, 0), (1, 0), (1, 1), (2, 1), (2, 2), (3, 2)],
+        [(0, 0), (1, 0), (2, 0), (2, 1), (3, 1), (4, 1)],
+    ];
+
+    fn net(faces: &[(i64, i64); 6], size: i64) -> CubeNet {
+        let faces: Vec<Coord<i64>> = faces.iter().map(|(x, y)| Coord::new(*x, *y)).collect();
+        CubeNet::new(&faces, size)
+    }
+
+    fn cells(net: &CubeNet) -> Vec<Coord<i64>> {
+        let size = net.size();
+        net.face_origins()
+            .flat_map(|o| (0..size).flat_map(move |y| (0..size).map(move |x| o + Coord::new(x, y))))
+            .collect()
+    }
+
+    #[test]
+    fn test_monkey_map() {
+        let map = indoc! {"
+                    ...#
+                    .#..
+                    #...
+                    ....
+            ...#.......#
+            ........#...
+            ..#....#....
+            ..........#.
+                    ...#....
+                    .....#..
+                    .#......
+                    ......#.
+        "};
+        let cells = map.lines().enumerate().flat_map(|(y, line)| {
+            line.chars()
+                .enumerate()
+                .filter(|(_, c)| *c != ' ')
+                .map(move |(x, _)| Coord::new(x as i64, y as i64))
+        });
+        let net = CubeNet::from_cells(cells);
+        assert_eq!(net.size(), 4);
+
+        assert_eq!(
+            net.step(&Coord::new(11, 5), Direction::East),
+            (Coord::new(14, 8), Direction::South)
+        );
+        assert_eq!(
+            net.step(&Coord::new(10, 11), Direction::South),
+            (Coord::new(1, 7), Direction::North)
+        );
+        assert_eq!(
+            net.step(&Coord::new(6, 4), Direction::North),
+            (Coord::new(8, 2), Direction::East)
+        );
+
+        // the top face's east side is glued to the east side of the bottom right
+        assert_eq!(
+            net.edge(0, Direction::East),
+            Edge {
+                face: 5,
+                side: Direction::East,
+                turns: 2
+            }
+        );
+    }
+
+    #[test]
+    fn test_edges_pair_up() {
+        for faces in &NETS {
+            let net = net(faces, 3);
+            for face in 0..6 {
+                for direction in DIRECTIONS {
+                    let edge = net.edge(face, direction);
+                    assert_ne!(edge.face, face);
+                    let back = net.edge(edge.face, edge.side);
+                    assert_eq!((back.face, back.side), (face, direction));
+                    assert_eq!((edge.turns + back.turns) % 4, 0);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_walks_loop_around() {
+        for faces in &NETS {
+            for size in 1..=3 {
+                let net = net(faces, size);
+                for start in cells(&net) {
+                    for facing in DIRECTIONS {
+                        // straight lines go all the way around and back
+                        let (mut coord, mut dir) = (start, facing);
+                        for _ in 0..4 * size {
+                            (coord, dir) = net.step(&coord, dir);
+                            assert!(net.contains(&coord));
+                        }
+                        assert_eq!((coord, dir), (start, facing));
+
+                        // and every step can be retraced
+                        let (next, dir) = net.step(&start, facing);
+                        let (back, dir) = net.step(&next, dir.opposite());
+                        assert_eq!((back, dir.opposite()), (start, facing));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Not a cube net")]
+    fn test_not_a_net() {
+        net(&[(0, 0), (1, 0), (2, 0), (3, 0), (4, 0), (0, 1)], 2);
+    }
+}
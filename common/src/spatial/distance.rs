@@ -0,0 +1,286 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, VecDeque},
+    fmt::Debug,
+    str::FromStr,
+};
+
+use num::PrimInt;
+
+use super::{Connectivity, Grid};
+
+#[derive(Clone, Debug)]
+pub struct DistanceField<T> {
+    pub distances: Grid<T, usize>,
+    pub predecessors: Option<Grid<T, usize>>,
+    // cumulative cell counts, indexed by distance
+    within: Vec<usize>,
+    within_same_parity: Vec<usize>,
+}
+
+impl<T> DistanceField<T>
+where
+    T: FromStr + PrimInt,
+    <T as FromStr>::Err: Debug,
+{
+    fn new(distances: Grid<T, usize>, predecessors: Option<Grid<T, usize>>) -> Self {
+        let max = distances.iter().map(|(_, d)| *d).max().unwrap_or(0);
+
+        let mut at = vec![0; max + 1];
+        for (_, d) in distances.iter() {
+            at[*d] += 1;
+        }
+
+        let mut within = at.clone();
+        let mut within_same_parity = at;
+        for d in 1..=max {
+            within[d] += within[d - 1];
+            if d >= 2 {
+                within_same_parity[d] += within_same_parity[d - 2];
+            }
+        }
+
+        Self {
+            distances,
+            predecessors,
+            within,
+            within_same_parity,
+        }
+    }
+
+    pub fn distance(&self, index: usize) -> Option<usize> {
+        *self.distances.get(index)
+    }
+
+    pub fn max_distance(&self) -> usize {
+        self.within.len() - 1
+    }
+
+    /// Number of reached cells at most `radius` away.
+    pub fn count_within(&self, radius: usize) -> usize {
+        self.within[radius.min(self.max_distance())]
+    }
+
+    /// Number of reached cells at most `steps` away with the same parity as
+    /// `steps`, ie. the cells you could be standing on after exactly `steps`
+    /// moves if backtracking is allowed.
+    pub fn count_reachable_in_exactly(&self, steps: usize) -> usize {
+        let max = self.max_distance();
+        if steps <= max {
+            self.within_same_parity[steps]
+        } else if steps % 2 == max % 2 {
+            self.within_same_parity[max]
+        } else if max > 0 {
+            self.within_same_parity[max - 1]
+        } else {
+            0
+        }
+    }
+
+    pub fn within(&self, radius: usize) -> impl Iterator<Item = usize> + '_ {
+        self.distances
+            .iter()
+            .filter(move |(_, d)| **d <= radius)
+            .map(|(i, _)| i)
+    }
+
+    /// Path from the nearest source to `index`, inclusive of both ends. Only
+    /// available if predecessors were tracked.
+    pub fn path_to(&self, index: usize) -> Option<Vec<usize>> {
+        let predecessors = self.predecessors.as_ref()?;
+        self.distance(index)?;
+
+        let mut path = vec![index];
+        let mut curr = index;
+        while let Some(prev) = predecessors.get(curr) {
+            path.push(*prev);
+            curr = *prev;
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+/// Breadth-first distances from every source to each cell, moving between
+/// neighbours when `passable(from, to)` allows it.
+pub fn distance_field<T, V, F>(
+    grid: &Grid<T, V>,
+    sources: &[usize],
+    connectivity: Connectivity,
+    track_predecessors: bool,
+    mut passable: F,
+) -> DistanceField<T>
+where
+    T: FromStr + PrimInt,
+    <T as FromStr>::Err: Debug,
+    F: FnMut(usize, usize) -> bool,
+{
+    let mut distances: Grid<T, usize> = Grid::new(grid.bounds);
+    let mut predecessors: Option<Grid<T, usize>> =
+        track_predecessors.then(|| Grid::new(grid.bounds));
+
+    let mut queue = VecDeque::new();
+    for source in sources {
+        if distances.get(*source).is_none() {
+            distances.set(*source, 0);
+            queue.push_back(*source);
+        }
+    }
+
+    while let Some(curr) = queue.pop_front() {
+        let next_distance = distances.get(curr).unwrap() + 1;
+        for neighbour in grid.connected_neighbours(curr, connectivity) {
+            if distances.get(neighbour).is_none() && passable(curr, neighbour) {
+                distances.set(neighbour, next_distance);
+                if let Some(p) = predecessors.as_mut() {
+                    p.set(neighbour, curr);
+                }
+                queue.push_back(neighbour);
+            }
+        }
+    }
+
+    DistanceField::new(distances, predecessors)
+}
+
+/// Like `distance_field`, but each move costs `step_cost(from, to)`, or is
+/// impassable if that returns `None`.
+pub fn weighted_distance_field<T, V, F>(
+    grid: &Grid<T, V>,
+    sources: &[usize],
+    connectivity: Connectivity,
+    track_predecessors: bool,
+    mut step_cost: F,
+) -> DistanceField<T>
+where
+    T: FromStr + PrimInt,
+    <T as FromStr>::Err: Debug,
+    F: FnMut(usize, usize) -> Option<usize>,
+{
+    let mut distances: Grid<T, usize> = Grid::new(grid.bounds);
+    let mut predecessors: Option<Grid<T, usize>> =
+        track_predecessors.then(|| Grid::new(grid.bounds));
+
+    let mut queue = BinaryHeap::new();
+    for source in sources {
+        distances.set(*source, 0);
+        queue.push(Reverse((0, *source)));
+    }
+
+    while let Some(Reverse((distance, curr))) = queue.pop() {
+        if *distances.get(curr) != Some(distance) {
+            // stale entry, already reached more cheaply
+            continue;
+        }
+
+        for neighbour in grid.connected_neighbours(curr, connectivity) {
+            if let Some(cost) = step_cost(curr, neighbour) {
+                let next_distance = distance + cost;
+                let better = match distances.get(neighbour) {
+                    Some(existing) => next_distance < *existing,
+                    None => true,
+                };
+                if better {
+                    distances.set(neighbour, next_distance);
+                    if let Some(p) = predecessors.as_mut() {
+                        p.set(neighbour, curr);
+                    }
+                    queue.push(Reverse((next_distance, neighbour)));
+                }
+            }
+        }
+    }
+
+    DistanceField::new(distances, predecessors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spatial::{Bounds, Coord};
+
+    use indoc::indoc;
+
+    const EXAMPLE: &str = indoc! {"
+        ...........
+        .....###.#.
+        .###.##..#.
+        ..#.#...#..
+        ....#.#....
+        .##..S####.
+        .##..#...#.
+        .......##..
+        .##.#.####.
+        .##..##.##.
+        ...........
+    "};
+
+    fn parse(input: &str) -> (Grid<usize, bool>, usize) {
+        let mut start = None;
+        let grid = Grid::parse(input, |c, coord| {
+            if *c == 'S' {
+                start = Some(*coord);
+            }
+            Some(*c == '#')
+        });
+        let start = grid.coord_to_index(&start.unwrap());
+        (grid, start)
+    }
+
+    #[test]
+    fn test_distance_field() {
+        let (grid, start) = parse(EXAMPLE);
+        let field = distance_field(&grid, &[start], Connectivity::Four, true, |_, to| {
+            *grid.get(to) == Some(false)
+        });
+
+        assert_eq!(field.count_reachable_in_exactly(1), 2);
+        assert_eq!(field.count_reachable_in_exactly(2), 4);
+        assert_eq!(field.count_reachable_in_exactly(3), 6);
+        assert_eq!(field.count_reachable_in_exactly(6), 16);
+        assert_eq!(field.count_reachable_in_exactly(100), 42);
+        assert_eq!(field.count_reachable_in_exactly(101), 39);
+        assert_eq!(field.count_within(1), 3);
+
+        // the bottom-right corner is 14 away, via a path of 15 cells
+        let corner = grid.coord_to_index(&Coord::new(10, 10));
+        assert_eq!(field.distance(corner), Some(14));
+        let path = field.path_to(corner).unwrap();
+        assert_eq!(path.len(), 15);
+        assert_eq!(path[0], start);
+        assert_eq!(path[14], corner);
+
+        // walls are never reached
+        assert_eq!(field.distance(grid.coord_to_index(&Coord::new(5, 1))), None);
+    }
+
+    #[test]
+    fn test_distance_field_multiple_sources() {
+        let grid: Grid<usize, ()> = Grid::new(Bounds::new(0, 4, 0, 0));
+        let field = distance_field(&grid, &[0, 4], Connectivity::Four, false, |_, _| true);
+        let distances: Vec<usize> = field.distances.iter().map(|(_, d)| *d).collect();
+        assert_eq!(distances, vec![0, 1, 2, 1, 0]);
+        assert_eq!(field.path_to(2), None);
+        assert_eq!(field.within(1).collect::<Vec<usize>>(), vec![0, 1, 3, 4]);
+    }
+
+    #[test]
+    fn test_weighted_distance_field() {
+        let grid: Grid<usize, u8> = Grid::parse("1163\n1381\n2136\n", |c, _| {
+            Some(c.to_digit(10).unwrap() as u8)
+        });
+        let field = weighted_distance_field(&grid, &[0], Connectivity::Four, true, |_, to| {
+            grid.get(to).map(|v| v as usize)
+        });
+        assert_eq!(field.distance(11), Some(13));
+
+        let path = field.path_to(11).unwrap();
+        assert_eq!(path.first(), Some(&0));
+        assert_eq!(path.last(), Some(&11));
+        let cost: usize = path[1..]
+            .iter()
+            .map(|i| grid.get(*i).unwrap() as usize)
+            .sum();
+        assert_eq!(cost, 13);
+    }
+}
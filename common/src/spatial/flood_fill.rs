@@ -0,0 +1,253 @@
+use std::{collections::VecDeque, fmt::Debug, str::FromStr};
+
+use num::PrimInt;
+
+use super::{Bounds, Grid};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Connectivity {
+    Four,
+    Eight,
+}
+
+#[derive(Clone, Debug)]
+pub struct Region<K> {
+    pub key: K,
+    pub size: usize,
+    pub touches_border: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct Regions<T, K> {
+    pub labels: Grid<T, usize>,
+    pub regions: Vec<Region<K>>,
+}
+
+impl<T, K> Regions<T, K> {
+    pub fn bounded(&self) -> impl Iterator<Item = &Region<K>> {
+        self.regions.iter().filter(|r| !r.touches_border)
+    }
+}
+
+impl<T, V> Grid<T, V>
+where
+    T: FromStr + PrimInt,
+    <T as FromStr>::Err: Debug,
+{
+    pub fn bounds(&self) -> &Bounds<T> {
+        &self.bounds
+    }
+
+    pub fn is_border(&self, index: usize) -> bool {
+        let x = index % self.width;
+        let y = index / self.width;
+        x == 0 || y == 0 || x == self.width - 1 || y == self.height - 1
+    }
+
+    // left, right, up, down, then up-left, up-right, down-left, down-right
+    pub fn neighbours_with_diagonals(&self, index: usize) -> [Option<usize>; 8] {
+        let x = index % self.width;
+        let y = index / self.width;
+        let [left, right, up, down] = self.neighbours(index);
+
+        let mut out = [left, right, up, down, None, None, None, None];
+
+        if x > 0 && y > 0 {
+            out[4] = Some(index - self.width - 1);
+        }
+        if x < self.width - 1 && y > 0 {
+            out[5] = Some(index - self.width + 1);
+        }
+        if x > 0 && y < self.height - 1 {
+            out[6] = Some(index + self.width - 1);
+        }
+        if x < self.width - 1 && y < self.height - 1 {
+            out[7] = Some(index + self.width + 1);
+        }
+
+        out
+    }
+
+    pub fn connected_neighbours(
+        &self,
+        index: usize,
+        connectivity: Connectivity,
+    ) -> impl Iterator<Item = usize> {
+        let neighbours = self.neighbours_with_diagonals(index);
+        let len = match connectivity {
+            Connectivity::Four => 4,
+            Connectivity::Eight => 8,
+        };
+        neighbours.into_iter().take(len).flatten()
+    }
+
+    /// Indices reachable from `seed` through passable cells, in BFS order.
+    /// Returns nothing if the seed itself isn't passable.
+    pub fn flood_fill<F>(
+        &self,
+        seed: usize,
+        connectivity: Connectivity,
+        mut passable: F,
+    ) -> Vec<usize>
+    where
+        F: FnMut(usize, &Option<V>) -> bool,
+    {
+        let mut seen = vec![false; self.cells.len()];
+        let mut out = vec![];
+
+        if !passable(seed, self.get(seed)) {
+            return out;
+        }
+
+        let mut queue = VecDeque::from([seed]);
+        seen[seed] = true;
+
+        while let Some(curr) = queue.pop_front() {
+            out.push(curr);
+            for neighbour in self.connected_neighbours(curr, connectivity) {
+                if !seen[neighbour] {
+                    seen[neighbour] = true;
+                    if passable(neighbour, self.get(neighbour)) {
+                        queue.push_back(neighbour);
+                    }
+                }
+            }
+        }
+
+        out
+    }
+
+    pub fn connected_components<F>(
+        &self,
+        connectivity: Connectivity,
+        mut passable: F,
+    ) -> Regions<T, ()>
+    where
+        F: FnMut(usize, &Option<V>) -> bool,
+    {
+        self.label_regions(connectivity, |i, val| passable(i, val).then_some(()))
+    }
+
+    /// Labels each cell with the region it belongs to. Neighbouring cells join
+    /// the same region when `key` returns equal values for both; cells where
+    /// `key` returns `None` are impassable and left unlabelled.
+    pub fn label_regions<K, F>(&self, connectivity: Connectivity, mut key: F) -> Regions<T, K>
+    where
+        K: Eq,
+        F: FnMut(usize, &Option<V>) -> Option<K>,
+    {
+        let keys: Vec<Option<K>> = self
+            .cells
+            .iter()
+            .enumerate()
+            .map(|(i, val)| key(i, val))
+            .collect();
+
+        let mut labels: Grid<T, usize> = Grid::new(self.bounds);
+        let mut regions = vec![];
+        let mut queue = VecDeque::new();
+
+        for (start, maybe_key) in keys.iter().enumerate() {
+            if maybe_key.is_none() || labels.get(start).is_some() {
+                continue;
+            }
+
+            let label = regions.len();
+            let mut size = 0;
+            let mut touches_border = false;
+
+            labels.set(start, label);
+            queue.push_back(start);
+
+            while let Some(curr) = queue.pop_front() {
+                size += 1;
+                touches_border |= self.is_border(curr);
+
+                for neighbour in self.connected_neighbours(curr, connectivity) {
+                    if labels.get(neighbour).is_none() && keys[neighbour] == *maybe_key {
+                        labels.set(neighbour, label);
+                        queue.push_back(neighbour);
+                    }
+                }
+            }
+
+            regions.push((start, size, touches_border));
+        }
+
+        // pull the keys out of the lookup table now that labelling is done
+        let mut keys = keys;
+        let regions = regions
+            .into_iter()
+            .map(|(start, size, touches_border)| Region {
+                key: keys[start].take().unwrap(),
+                size,
+                touches_border,
+            })
+            .collect();
+
+        Regions { labels, regions }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use indoc::indoc;
+
+    const EXAMPLE: &str = indoc! {"
+        ##..#
+        #.#.#
+        ###..
+        ..#.#
+        .#.##
+    "};
+
+    fn parse(input: &str) -> Grid<usize, bool> {
+        Grid::parse(input, |c, _| Some(*c == '#'))
+    }
+
+    #[test]
+    fn test_flood_fill() {
+        let grid = parse(EXAMPLE);
+        let filled = grid.flood_fill(2, Connectivity::Four, |_, val| *val == Some(false));
+        assert_eq!(filled.len(), 6);
+        assert_eq!(filled[0], 2);
+
+        let walls = grid.flood_fill(0, Connectivity::Four, |_, val| *val == Some(true));
+        assert_eq!(walls.len(), 8);
+
+        let walls = grid.flood_fill(0, Connectivity::Eight, |_, val| *val == Some(true));
+        assert_eq!(walls.len(), 12);
+
+        let none = grid.flood_fill(0, Connectivity::Four, |_, val| *val == Some(false));
+        assert!(none.is_empty());
+    }
+
+    #[test]
+    fn test_connected_components() {
+        let grid = parse(EXAMPLE);
+        let components = grid.connected_components(Connectivity::Four, |_, val| *val == Some(true));
+        let sizes: Vec<usize> = components.regions.iter().map(|r| r.size).collect();
+        assert_eq!(sizes, vec![8, 2, 3, 1]);
+        assert_eq!(components.labels.get(0), &Some(0));
+        assert_eq!(components.labels.get(2), &None);
+        assert_eq!(components.labels.get(24), &Some(2));
+
+        let components =
+            grid.connected_components(Connectivity::Eight, |_, val| *val == Some(true));
+        let sizes: Vec<usize> = components.regions.iter().map(|r| r.size).collect();
+        assert_eq!(sizes, vec![12, 2]);
+    }
+
+    #[test]
+    fn test_label_regions() {
+        let grid = parse(EXAMPLE);
+        let regions = grid.label_regions(Connectivity::Four, |_, val| *val);
+        assert_eq!(regions.regions.len(), 8);
+        assert_eq!(regions.regions.iter().map(|r| r.size).sum::<usize>(), 25);
+
+        let bounded: Vec<(bool, usize)> = regions.bounded().map(|r| (r.key, r.size)).collect();
+        assert_eq!(bounded, vec![(false, 1)]);
+    }
+}
@@ -0,0 +1,421 @@
+use std::cmp;
+
+use num::PrimInt;
+
+/// An inclusive range of values, `start..=end`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Interval<T> {
+    pub start: T,
+    pub end: T,
+}
+
+impl<T> Interval<T>
+where
+    T: PrimInt,
+{
+    pub fn new(start: T, end: T) -> Self {
+        assert!(start <= end, "Interval ends before it starts");
+        Self { start, end }
+    }
+
+    /// Covering `len` values from `start`, which must be at least one.
+    pub fn from_len(start: T, len: T) -> Self {
+        Self::new(start, start + len - T::one())
+    }
+
+    pub fn contains(&self, val: T) -> bool {
+        self.start <= val && val <= self.end
+    }
+
+    /// Number of values covered, widened so the full range of a 64-bit type
+    /// still fits.
+    pub fn size(&self) -> u128 {
+        (self.end.to_i128().unwrap() - self.start.to_i128().unwrap() + 1) as u128
+    }
+
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let start = cmp::max(self.start, other.start);
+        let end = cmp::min(self.end, other.end);
+        (start <= end).then_some(Self { start, end })
+    }
+
+    // overlapping, or with no gap between them
+    fn touches(&self, next: &Self) -> bool {
+        self.end == T::max_value() || next.start <= self.end + T::one()
+    }
+}
+
+/// A set of values stored as sorted, disjoint intervals. Neighbouring
+/// intervals always have a gap between them, so equal sets compare equal.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct IntervalSet<T> {
+    intervals: Vec<Interval<T>>,
+}
+
+impl<T> IntervalSet<T>
+where
+    T: PrimInt,
+{
+    pub fn new() -> Self {
+        Self { intervals: vec![] }
+    }
+
+    fn normalised(mut intervals: Vec<Interval<T>>) -> Self {
+        intervals.sort_by_key(|i| i.start);
+
+        let mut out: Vec<Interval<T>> = Vec::with_capacity(intervals.len());
+        for next in intervals {
+            match out.last_mut() {
+                Some(last) if last.touches(&next) => last.end = cmp::max(last.end, next.end),
+                _ => out.push(next),
+            }
+        }
+
+        Self { intervals: out }
+    }
+
+    pub fn intervals(&self) -> &[Interval<T>] {
+        &self.intervals
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    pub fn min(&self) -> Option<T> {
+        self.intervals.first().map(|i| i.start)
+    }
+
+    pub fn max(&self) -> Option<T> {
+        self.intervals.last().map(|i| i.end)
+    }
+
+    /// Total number of values in the set.
+    pub fn size(&self) -> u128 {
+        self.intervals.iter().map(|i| i.size()).sum()
+    }
+
+    pub fn contains(&self, val: T) -> bool {
+        // the last interval starting at or before val is the only candidate
+        let after = self.intervals.partition_point(|i| i.start <= val);
+        after > 0 && self.intervals[after - 1].contains(val)
+    }
+
+    pub fn insert(&mut self, interval: Interval<T>) {
+        let mut intervals = std::mem::take(&mut self.intervals);
+        intervals.push(interval);
+        *self = Self::normalised(intervals);
+    }
+
+    pub fn remove(&mut self, interval: Interval<T>) {
+        *self = self.difference(&Self::from(interval));
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        Self::normalised(
+            self.intervals
+                .iter()
+                .chain(other.intervals.iter())
+                .copied()
+                .collect(),
+        )
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let (a, b) = (&self.intervals, &other.intervals);
+        let mut out = vec![];
+        let (mut i, mut j) = (0, 0);
+        while i < a.len() && j < b.len() {
+            out.extend(a[i].intersection(&b[j]));
+            // drop whichever finishes first, as it can't meet anything else
+            if a[i].end < b[j].end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        Self { intervals: out }
+    }
+
+    /// Every value in `within` that isn't in the set.
+    pub fn complement(&self, within: &Interval<T>) -> Self {
+        let mut out = vec![];
+        let mut next_start = Some(within.start);
+
+        for interval in &self.intervals {
+            let Some(start) = next_start else { break };
+            if interval.start > start {
+                let gap = Interval::new(start, interval.start - T::one());
+                out.extend(gap.intersection(within));
+            }
+            next_start =
+                (interval.end < T::max_value()).then(|| cmp::max(start, interval.end + T::one()));
+        }
+
+        if let Some(start) = next_start {
+            if start <= within.end {
+                out.push(Interval::new(start, within.end));
+            }
+        }
+
+        Self { intervals: out }
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        match (self.min(), self.max()) {
+            (Some(min), Some(max)) => {
+                self.intersection(&other.complement(&Interval::new(min, max)))
+            }
+            _ => Self::new(),
+        }
+    }
+
+    /// Sends the set through each map in turn.
+    pub fn remap(&self, maps: &[IntervalMap<T>]) -> Self {
+        maps.iter().fold(self.clone(), |set, map| map.apply(&set))
+    }
+}
+
+impl<T> From<Interval<T>> for IntervalSet<T> {
+    fn from(interval: Interval<T>) -> Self {
+        Self {
+            intervals: vec![interval],
+        }
+    }
+}
+
+impl<T> FromIterator<Interval<T>> for IntervalSet<T>
+where
+    T: PrimInt,
+{
+    fn from_iter<I: IntoIterator<Item = Interval<T>>>(iter: I) -> Self {
+        Self::normalised(iter.into_iter().collect())
+    }
+}
+
+/// A piecewise-linear map: each source interval is shifted to start at its
+/// destination, and values outside every source interval map to themselves.
+#[derive(Clone, Debug, Default)]
+pub struct IntervalMap<T> {
+    mappings: Vec<(Interval<T>, T)>,
+}
+
+impl<T> IntervalMap<T>
+where
+    T: PrimInt,
+{
+    pub fn new() -> Self {
+        Self { mappings: vec![] }
+    }
+
+    pub fn insert(&mut self, source: Interval<T>, destination_start: T) {
+        assert!(
+            self.mappings
+                .iter()
+                .all(|(s, _)| s.intersection(&source).is_none()),
+            "Source intervals overlap"
+        );
+        self.mappings.push((source, destination_start));
+    }
+
+    fn shift(val: T, source: &Interval<T>, destination_start: T) -> T {
+        val - source.start + destination_start
+    }
+
+    pub fn apply_value(&self, val: T) -> T {
+        self.mappings
+            .iter()
+            .find(|(source, _)| source.contains(val))
+            .map(|(source, dest)| Self::shift(val, source, *dest))
+            .unwrap_or(val)
+    }
+
+    pub fn apply(&self, set: &IntervalSet<T>) -> IntervalSet<T> {
+        let sources: IntervalSet<T> = self.mappings.iter().map(|(s, _)| *s).collect();
+        let mut out = set.difference(&sources).intervals;
+
+        for (source, dest) in &self.mappings {
+            let moved = set.intersection(&IntervalSet::from(*source));
+            out.extend(moved.intervals.iter().map(|i| Interval {
+                start: Self::shift(i.start, source, *dest),
+                end: Self::shift(i.end, source, *dest),
+            }));
+        }
+
+        IntervalSet::normalised(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use indoc::indoc;
+
+    fn set(intervals: &[(i32, i32)]) -> IntervalSet<i32> {
+        intervals
+            .iter()
+            .map(|(start, end)| Interval::new(*start, *end))
+            .collect()
+    }
+
+    #[test]
+    fn test_normalise() {
+        let s = set(&[(5, 8), (1, 2), (3, 4), (7, 10), (20, 20)]);
+        assert_eq!(s, set(&[(1, 10), (20, 20)]));
+        assert_eq!(s.size(), 11);
+        assert!(s.contains(10));
+        assert!(!s.contains(11));
+        assert!(!s.contains(0));
+        assert!(s.contains(20));
+
+        let mut s = s;
+        s.insert(Interval::new(11, 19));
+        assert_eq!(s, set(&[(1, 20)]));
+        s.remove(Interval::new(5, 6));
+        assert_eq!(s, set(&[(1, 4), (7, 20)]));
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let a = set(&[(0, 5), (10, 15)]);
+        let b = set(&[(3, 12), (20, 25)]);
+
+        assert_eq!(a.union(&b), set(&[(0, 15), (20, 25)]));
+        assert_eq!(a.intersection(&b), set(&[(3, 5), (10, 12)]));
+        assert_eq!(a.difference(&b), set(&[(0, 2), (13, 15)]));
+        assert_eq!(b.difference(&a), set(&[(6, 9), (20, 25)]));
+        assert_eq!(
+            a.complement(&Interval::new(-5, 30)),
+            set(&[(-5, -1), (6, 9), (16, 30)])
+        );
+        assert_eq!(a.complement(&Interval::new(2, 12)), set(&[(6, 9)]));
+        assert!(a.complement(&Interval::new(1, 4)).is_empty());
+    }
+
+    #[test]
+    fn test_extremes() {
+        let full: IntervalSet<u8> = IntervalSet::from(Interval::new(0, 255));
+        assert_eq!(full.size(), 256);
+        assert!(full.complement(&Interval::new(0, 255)).is_empty());
+
+        let top: IntervalSet<u8> = [Interval::new(250, 255), Interval::new(0, 3)]
+            .into_iter()
+            .collect();
+        assert_eq!(
+            top.complement(&Interval::new(0, 255)),
+            IntervalSet::from(Interval::new(4, 249))
+        );
+    }
+
+    #[test]
+    fn test_row_coverage() {
+        // the beacon exclusion zone example, looking at row 10
+        let sensors = [
+            ((2, 18), 7),
+            ((9, 16), 1),
+            ((13, 2), 3),
+            ((12, 14), 4),
+            ((10, 20), 4),
+            ((14, 17), 5),
+            ((8, 7), 9),
+            ((2, 0), 10),
+            ((0, 11), 3),
+            ((20, 14), 8),
+            ((17, 20), 6),
+            ((16, 7), 5),
+            ((14, 3), 1),
+            ((20, 1), 7),
+        ];
+        let row: i32 = 10;
+        let covered: IntervalSet<i32> = sensors
+            .iter()
+            .filter_map(|((x, y), distance)| {
+                let spare = distance - (row - y).abs();
+                (spare >= 0).then(|| Interval::new(x - spare, x + spare))
+            })
+            .collect();
+
+        // less the one beacon on that row
+        let mut covered = covered;
+        covered.remove(Interval::new(2, 2));
+        assert_eq!(covered.size(), 26);
+    }
+
+    const ALMANAC: &str = indoc! {"
+        seeds: 79 14 55 13
+
+        seed-to-soil map:
+        50 98 2
+        52 50 48
+
+        soil-to-fertilizer map:
+        0 15 37
+        37 52 2
+        39 0 15
+
+        fertilizer-to-water map:
+        49 53 8
+        0 11 42
+        42 0 7
+        57 7 4
+
+        water-to-light map:
+        88 18 7
+        18 25 70
+
+        light-to-temperature map:
+        45 77 23
+        81 45 19
+        68 64 13
+
+        temperature-to-humidity map:
+        0 69 1
+        1 0 69
+
+        humidity-to-location map:
+        60 56 37
+        56 93 4
+    "};
+
+    fn parse_almanac(input: &str) -> (Vec<u64>, Vec<IntervalMap<u64>>) {
+        let mut sections = input.split("\n\n");
+        let seeds = sections.next().unwrap()[7..]
+            .split(' ')
+            .map(|n| n.parse().unwrap())
+            .collect();
+        let maps = sections
+            .map(|section| {
+                let mut map = IntervalMap::new();
+                for line in section.lines().skip(1) {
+                    let nums: Vec<u64> = line.split(' ').map(|n| n.parse().unwrap()).collect();
+                    map.insert(Interval::from_len(nums[1], nums[2]), nums[0]);
+                }
+                map
+            })
+            .collect();
+        (seeds, maps)
+    }
+
+    #[test]
+    fn test_remap_values() {
+        let (seeds, maps) = parse_almanac(ALMANAC);
+        let locations: Vec<u64> = seeds
+            .iter()
+            .map(|seed| maps.iter().fold(*seed, |val, map| map.apply_value(val)))
+            .collect();
+        assert_eq!(locations, vec![82, 43, 86, 35]);
+    }
+
+    #[test]
+    fn test_remap_ranges() {
+        let (seeds, maps) = parse_almanac(ALMANAC);
+        let seeds: IntervalSet<u64> = seeds
+            .chunks(2)
+            .map(|pair| Interval::from_len(pair[0], pair[1]))
+            .collect();
+        let locations = seeds.remap(&maps);
+        assert_eq!(locations.min(), Some(46));
+        assert_eq!(locations.size(), 27);
+    }
+}
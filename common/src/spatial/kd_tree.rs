@@ -0,0 +1,300 @@
+use std::{cmp::Ordering, fmt::Debug, str::FromStr};
+
+use num::PrimInt;
+
+use super::Coord;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Metric {
+    Manhattan,
+    /// Distances are kept squared, so they stay exact integers.
+    SquaredEuclidean,
+}
+
+impl Metric {
+    fn distance(&self, dx: i128, dy: i128) -> i128 {
+        match self {
+            Metric::Manhattan => dx.abs() + dy.abs(),
+            Metric::SquaredEuclidean => dx * dx + dy * dy,
+        }
+    }
+}
+
+/// Every point sharing the smallest distance from a query.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Nearest {
+    pub distance: i128,
+    pub indices: Vec<usize>,
+}
+
+impl Nearest {
+    pub fn is_tie(&self) -> bool {
+        self.indices.len() > 1
+    }
+}
+
+/// A 2D k-d tree over a fixed set of points, answering queries with indices
+/// into the slice it was built from.
+#[derive(Clone, Debug)]
+pub struct KdTree {
+    // (x, y, index), arranged so each subtree is a contiguous run with its
+    // splitting point in the middle
+    points: Vec<(i128, i128, usize)>,
+}
+
+impl KdTree {
+    pub fn new<T>(coords: &[Coord<T>]) -> Self
+    where
+        T: FromStr + PrimInt,
+        <T as FromStr>::Err: Debug,
+    {
+        let mut points: Vec<(i128, i128, usize)> = coords
+            .iter()
+            .enumerate()
+            .map(|(i, c)| (c.x.to_i128().unwrap(), c.y.to_i128().unwrap(), i))
+            .collect();
+        Self::build(&mut points, 0);
+        Self { points }
+    }
+
+    fn build(points: &mut [(i128, i128, usize)], depth: usize) {
+        if points.len() <= 1 {
+            return;
+        }
+        let mid = points.len() / 2;
+        points.select_nth_unstable_by_key(mid, |p| Self::axis(p, depth));
+        let (left, right) = points.split_at_mut(mid);
+        Self::build(left, depth + 1);
+        Self::build(&mut right[1..], depth + 1);
+    }
+
+    fn axis(point: &(i128, i128, usize), depth: usize) -> i128 {
+        match depth & 1 {
+            0 => point.0,
+            _ => point.1,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// All the points closest to `query`, or `None` if the tree is empty.
+    pub fn nearest<T>(&self, query: &Coord<T>, metric: Metric) -> Option<Nearest>
+    where
+        T: PrimInt,
+    {
+        let query = (query.x.to_i128().unwrap(), query.y.to_i128().unwrap());
+        let mut best: Option<Nearest> = None;
+        self.search_nearest(&self.points, 0, query, metric, &mut best);
+        if let Some(best) = &mut best {
+            best.indices.sort_unstable();
+        }
+        best
+    }
+
+    /// The closest point, as long as no other point is just as close.
+    pub fn nearest_unique<T>(&self, query: &Coord<T>, metric: Metric) -> Option<usize>
+    where
+        T: PrimInt,
+    {
+        self.nearest(query, metric)
+            .filter(|n| !n.is_tie())
+            .map(|n| n.indices[0])
+    }
+
+    fn search_nearest(
+        &self,
+        points: &[(i128, i128, usize)],
+        depth: usize,
+        query: (i128, i128),
+        metric: Metric,
+        best: &mut Option<Nearest>,
+    ) {
+        if points.is_empty() {
+            return;
+        }
+        let mid = points.len() / 2;
+        let point = &points[mid];
+
+        let distance = metric.distance(point.0 - query.0, point.1 - query.1);
+        match best.as_ref().map(|b| distance.cmp(&b.distance)) {
+            Some(Ordering::Greater) => (),
+            Some(Ordering::Equal) => best.as_mut().unwrap().indices.push(point.2),
+            _ => {
+                *best = Some(Nearest {
+                    distance,
+                    indices: vec![point.2],
+                })
+            }
+        }
+
+        let diff = Self::axis(&(query.0, query.1, 0), depth) - Self::axis(point, depth);
+        let (near, far) = if diff < 0 {
+            (&points[..mid], &points[mid + 1..])
+        } else {
+            (&points[mid + 1..], &points[..mid])
+        };
+        self.search_nearest(near, depth + 1, query, metric, best);
+
+        // the far side can only hold something as close if the splitting
+        // line itself is close enough, and ties matter too
+        let line_distance = match metric {
+            Metric::Manhattan => diff.abs(),
+            Metric::SquaredEuclidean => diff * diff,
+        };
+        match best {
+            Some(b) if line_distance > b.distance => (),
+            _ => self.search_nearest(far, depth + 1, query, metric, best),
+        }
+    }
+
+    /// Every point at most `distance` away from `query`. For
+    /// `SquaredEuclidean`, `distance` is squared too.
+    pub fn within<T>(&self, query: &Coord<T>, distance: i128, metric: Metric) -> Vec<usize>
+    where
+        T: PrimInt,
+    {
+        let query = (query.x.to_i128().unwrap(), query.y.to_i128().unwrap());
+        let mut out = vec![];
+        self.search_within(&self.points, 0, query, distance, metric, &mut out);
+        out.sort_unstable();
+        out
+    }
+
+    fn search_within(
+        &self,
+        points: &[(i128, i128, usize)],
+        depth: usize,
+        query: (i128, i128),
+        distance: i128,
+        metric: Metric,
+        out: &mut Vec<usize>,
+    ) {
+        if points.is_empty() {
+            return;
+        }
+        let mid = points.len() / 2;
+        let point = &points[mid];
+
+        if metric.distance(point.0 - query.0, point.1 - query.1) <= distance {
+            out.push(point.2);
+        }
+
+        let diff = Self::axis(&(query.0, query.1, 0), depth) - Self::axis(point, depth);
+        let line_distance = match metric {
+            Metric::Manhattan => diff.abs(),
+            Metric::SquaredEuclidean => diff * diff,
+        };
+        let (near, far) = if diff < 0 {
+            (&points[..mid], &points[mid + 1..])
+        } else {
+            (&points[mid + 1..], &points[..mid])
+        };
+        self.search_within(near, depth + 1, query, distance, metric, out);
+        if line_distance <= distance {
+            self.search_within(far, depth + 1, query, distance, metric, out);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coords(points: &[(i32, i32)]) -> Vec<Coord<i32>> {
+        points.iter().map(|(x, y)| Coord::new(*x, *y)).collect()
+    }
+
+    // checks every query against a brute force search
+    fn brute_nearest(points: &[Coord<i32>], query: &Coord<i32>, metric: Metric) -> Nearest {
+        let distances: Vec<i128> = points
+            .iter()
+            .map(|p| metric.distance((p.x - query.x) as i128, (p.y - query.y) as i128))
+            .collect();
+        let distance = *distances.iter().min().unwrap();
+        let indices = (0..points.len())
+            .filter(|i| distances[*i] == distance)
+            .collect();
+        Nearest { distance, indices }
+    }
+
+    #[test]
+    fn test_chronal_coordinates() {
+        let points = coords(&[(1, 1), (1, 6), (8, 3), (3, 4), (5, 5), (8, 9)]);
+        let tree = KdTree::new(&points);
+
+        assert_eq!(
+            tree.nearest_unique(&Coord::new(4, 3), Metric::Manhattan),
+            Some(3)
+        );
+        // equally far from B and D
+        let tie = tree.nearest(&Coord::new(0, 4), Metric::Manhattan).unwrap();
+        assert!(tie.is_tie());
+        assert_eq!(
+            tie,
+            Nearest {
+                distance: 3,
+                indices: vec![1, 3]
+            }
+        );
+        assert_eq!(
+            tree.nearest_unique(&Coord::new(0, 4), Metric::Manhattan),
+            None
+        );
+
+        assert_eq!(
+            tree.within(&Coord::new(4, 4), 2, Metric::Manhattan),
+            vec![3, 4]
+        );
+        assert_eq!(
+            tree.within(&Coord::new(4, 4), 2, Metric::SquaredEuclidean),
+            vec![3, 4]
+        );
+        assert_eq!(
+            tree.within(&Coord::new(4, 4), 1, Metric::SquaredEuclidean),
+            vec![3]
+        );
+    }
+
+    #[test]
+    fn test_against_brute_force() {
+        // a spread of points with plenty of duplicates and ties
+        let points: Vec<Coord<i32>> = (0..200)
+            .map(|i| Coord::new((i * 37) % 23 - 11, (i * 53) % 19 - 9))
+            .collect();
+        let tree = KdTree::new(&points);
+
+        for metric in [Metric::Manhattan, Metric::SquaredEuclidean] {
+            for y in -12..12 {
+                for x in -14..14 {
+                    let query = Coord::new(x, y);
+                    let expected = brute_nearest(&points, &query, metric);
+                    assert_eq!(tree.nearest(&query, metric), Some(expected));
+
+                    let within: Vec<usize> = (0..points.len())
+                        .filter(|i| {
+                            let p = &points[*i];
+                            metric.distance((p.x - x) as i128, (p.y - y) as i128) <= 5
+                        })
+                        .collect();
+                    assert_eq!(tree.within(&query, 5, metric), within);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_empty() {
+        let tree = KdTree::new::<u8>(&[]);
+        assert!(tree.is_empty());
+        assert_eq!(tree.nearest(&Coord::new(0u8, 0), Metric::Manhattan), None);
+        assert!(tree
+            .within(&Coord::new(0u8, 0), 10, Metric::Manhattan)
+            .is_empty());
+    }
+}
@@ -13,6 +13,30 @@ macro_rules! cast {
     };
 }
 
+mod compressed;
+mod distance;
+mod flood_fill;
+mod render;
+mod summed_area;
+
+pub mod automaton;
+pub mod bit_grid;
+pub mod box_set;
+pub mod clipping;
+pub mod cube_net;
+pub mod interval;
+pub mod kd_tree;
+pub mod ocr;
+pub mod polygon;
+pub mod rotation;
+pub mod segment;
+
+pub use compressed::*;
+pub use distance::*;
+pub use flood_fill::*;
+pub use render::*;
+pub use summed_area::*;
+
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub struct Coord<T> {
     pub x: T,
@@ -134,7 +158,7 @@ where
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum Direction {
     North,
     South,
@@ -164,6 +188,17 @@ impl Direction {
             East => North,
         }
     }
+
+    pub fn opposite(&self) -> Self {
+        use Direction::*;
+
+        match self {
+            North => South,
+            South => North,
+            West => East,
+            East => West,
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -219,6 +254,128 @@ where
     pub fn size(&self) -> T {
         self.width * self.height
     }
+
+    pub fn contains(&self, coord: &Coord<T>) -> bool {
+        coord.x >= self.left
+            && coord.x <= self.right
+            && coord.y >= self.top
+            && coord.y <= self.bottom
+    }
+
+    pub fn contains_bounds(&self, other: &Self) -> bool {
+        other.left >= self.left
+            && other.right <= self.right
+            && other.top >= self.top
+            && other.bottom <= self.bottom
+    }
+
+    pub fn try_coord_to_index<I: PrimInt>(&self, coord: &Coord<T>) -> Option<I> {
+        if self.contains(coord) {
+            Some(self.coord_to_index(coord))
+        } else {
+            None
+        }
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        Self::new(
+            self.left.min(other.left),
+            self.right.max(other.right),
+            self.top.min(other.top),
+            self.bottom.max(other.bottom),
+        )
+    }
+
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let left = self.left.max(other.left);
+        let right = self.right.min(other.right);
+        let top = self.top.max(other.top);
+        let bottom = self.bottom.min(other.bottom);
+        if left <= right && top <= bottom {
+            Some(Self::new(left, right, top, bottom))
+        } else {
+            None
+        }
+    }
+
+    // returns None if the margin would go past the limits of T
+    pub fn expand(&self, margin: T) -> Option<Self> {
+        Some(Self::new(
+            self.left.checked_sub(&margin)?,
+            self.right.checked_add(&margin)?,
+            self.top.checked_sub(&margin)?,
+            self.bottom.checked_add(&margin)?,
+        ))
+    }
+
+    // returns None if shrinking would leave nothing behind
+    pub fn shrink(&self, margin: T) -> Option<Self> {
+        let both_sides = margin.checked_add(&margin)?;
+        if both_sides >= self.width || both_sides >= self.height {
+            None
+        } else {
+            Some(Self::new(
+                self.left + margin,
+                self.right - margin,
+                self.top + margin,
+                self.bottom - margin,
+            ))
+        }
+    }
+
+    // the middle coord, rounding towards the top-left
+    pub fn centre(&self) -> Coord<T> {
+        let two = T::one() + T::one();
+        Coord::new(
+            self.left + (self.right - self.left) / two,
+            self.top + (self.bottom - self.top) / two,
+        )
+    }
+
+    pub fn clamp(&self, coord: &Coord<T>) -> Coord<T> {
+        Coord::new(
+            coord.x.clamp(self.left, self.right),
+            coord.y.clamp(self.top, self.bottom),
+        )
+    }
+
+    pub fn is_border(&self, coord: &Coord<T>) -> bool {
+        self.contains(coord)
+            && (coord.x == self.left
+                || coord.x == self.right
+                || coord.y == self.top
+                || coord.y == self.bottom)
+    }
+
+    // all coords in row-major order
+    pub fn coords(&self) -> impl Iterator<Item = Coord<T>> + '_ {
+        // widened first, as the area can overflow T even when the sides don't
+        let width: usize = cast!(self.width);
+        let height: usize = cast!(self.height);
+        (0..width * height).map(|i| self.index_to_coord(i))
+    }
+
+    // coords around the edge, clockwise from the top-left corner
+    pub fn border(&self) -> impl Iterator<Item = Coord<T>> + '_ {
+        let width: usize = cast!(self.width);
+        let height: usize = cast!(self.height);
+
+        let top = (0..width).map(move |x| (x, 0));
+        let right = (1..height).map(move |y| (width - 1, y));
+        let bottom = (0..width - 1)
+            .rev()
+            .filter(move |_| height > 1)
+            .map(move |x| (x, height - 1));
+        let left = (1..height - 1)
+            .rev()
+            .filter(move |_| width > 1)
+            .map(|y| (0, y));
+
+        top.chain(right)
+            .chain(bottom)
+            .chain(left)
+            .map(move |(x, y)| self.index_to_coord(y * width + x))
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -306,10 +463,19 @@ where
         self.bounds.index_to_coord(index)
     }
 
+    // takes an index from `coord_to_index` or a neighbour lookup, which is
+    // always on the grid, so an out-of-range index is a bug and panics;
+    // lookups by coordinate that may fall off the grid go through `get_at`
     pub fn get(&self, index: usize) -> &Option<V> {
         &self.cells[index]
     }
 
+    // like `get`, but out-of-bounds coords are treated as empty cells
+    pub fn get_at(&self, coord: &Coord<T>) -> Option<&V> {
+        let index: usize = self.bounds.try_coord_to_index(coord)?;
+        self.cells[index].as_ref()
+    }
+
     pub fn get_mut(&mut self, index: usize) -> &mut Option<V> {
         &mut self.cells[index]
     }
@@ -368,3 +534,94 @@ where
         out
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bounds_algebra() {
+        let a: Bounds<i32> = Bounds::new(0, 4, 0, 2);
+        let b: Bounds<i32> = Bounds::new(3, 6, -1, 1);
+
+        assert_eq!(a.union(&b), Bounds::new(0, 6, -1, 2));
+        assert_eq!(a.intersection(&b), Some(Bounds::new(3, 4, 0, 1)));
+        assert_eq!(a.intersection(&Bounds::new(5, 6, 0, 0)), None);
+
+        assert!(a.contains(&Coord::new(4, 2)));
+        assert!(!a.contains(&Coord::new(5, 2)));
+        assert!(!a.contains(&Coord::new(0, -1)));
+        assert!(a.union(&b).contains_bounds(&a));
+        assert!(!a.contains_bounds(&b));
+
+        assert_eq!(a.expand(2), Some(Bounds::new(-2, 6, -2, 4)));
+        assert_eq!(a.shrink(1), Some(Bounds::new(1, 3, 1, 1)));
+        assert_eq!(a.shrink(2), None);
+
+        // unsigned bounds can't grow past 0 or the maximum
+        let c: Bounds<u32> = Bounds::new(0, 4, 1, 3);
+        assert_eq!(c.expand(0), Some(c));
+        assert_eq!(c.expand(1), None);
+        assert_eq!(
+            Bounds::new(1, 4, 1, 3).expand(1),
+            Some(Bounds::new(0, 5, 0, 4))
+        );
+        assert_eq!(Bounds::new(1, u32::MAX - 1, 1, 3).expand(2), None);
+        assert_eq!(c.shrink(1), Some(Bounds::new(1, 3, 2, 2)));
+        assert_eq!(c.shrink(u32::MAX), None);
+
+        assert_eq!(a.centre(), Coord::new(2, 1));
+        assert_eq!(b.centre(), Coord::new(4, 0));
+        assert_eq!(a.clamp(&Coord::new(-3, 1)), Coord::new(0, 1));
+        assert_eq!(a.clamp(&Coord::new(7, 9)), Coord::new(4, 2));
+    }
+
+    #[test]
+    fn test_bounds_iteration() {
+        let bounds: Bounds<u8> = Bounds::new(1, 3, 5, 7);
+
+        let coords: Vec<Coord<u8>> = bounds.coords().collect();
+        assert_eq!(coords.len(), 9);
+        assert_eq!(coords[0], Coord::new(1, 5));
+        assert_eq!(coords[1], Coord::new(2, 5));
+        assert_eq!(coords[8], Coord::new(3, 7));
+
+        // more coords than fit in the coordinate type
+        let big: Bounds<u8> = Bounds::new(0, 99, 0, 9);
+        assert_eq!(big.coords().count(), 1000);
+
+        let border: Vec<(u8, u8)> = bounds.border().map(|c| (c.x, c.y)).collect();
+        assert_eq!(
+            border,
+            vec![
+                (1, 5),
+                (2, 5),
+                (3, 5),
+                (3, 6),
+                (3, 7),
+                (2, 7),
+                (1, 7),
+                (1, 6)
+            ]
+        );
+        assert!(border
+            .iter()
+            .all(|(x, y)| bounds.is_border(&Coord::new(*x, *y))));
+        assert!(!bounds.is_border(&Coord::new(2, 6)));
+
+        let column: Bounds<u8> = Bounds::new(0, 0, 0, 2);
+        assert_eq!(column.border().count(), 3);
+        let row: Bounds<u8> = Bounds::new(0, 2, 0, 0);
+        assert_eq!(row.border().count(), 3);
+        let single: Bounds<u8> = Bounds::new(4, 4, 4, 4);
+        assert_eq!(single.border().collect::<Vec<_>>(), vec![Coord::new(4, 4)]);
+    }
+
+    #[test]
+    fn test_grid_get_at() {
+        let grid: Grid<u8, char> = Grid::parse("ab\ncd\n", |c, _| (*c != 'c').then_some(*c));
+        assert_eq!(grid.get_at(&Coord::new(1, 1)), Some(&'d'));
+        assert_eq!(grid.get_at(&Coord::new(0, 1)), None);
+        assert_eq!(grid.get_at(&Coord::new(2, 0)), None);
+    }
+}
@@ -0,0 +1,318 @@
+use std::{fmt::Debug, str::FromStr};
+
+use num::PrimInt;
+
+use super::{Bounds, Coord, Grid};
+
+struct Font {
+    height: usize,
+    // width of each character cell, including the spacing after the glyph
+    pitch: usize,
+    glyphs: &'static [(char, &'static str)],
+}
+
+const SMALL_FONT: Font = Font {
+    height: 6,
+    pitch: 5,
+    glyphs: &[
+        ('A', ".##.\n#..#\n#..#\n####\n#..#\n#..#"),
+        ('B', "###.\n#..#\n###.\n#..#\n#..#\n###."),
+        ('C', ".##.\n#..#\n#...\n#...\n#..#\n.##."),
+        ('E', "####\n#...\n###.\n#...\n#...\n####"),
+        ('F', "####\n#...\n###.\n#...\n#...\n#..."),
+        ('G', ".##.\n#..#\n#...\n#.##\n#..#\n.###"),
+        ('H', "#..#\n#..#\n####\n#..#\n#..#\n#..#"),
+        ('I', ".###\n..#.\n..#.\n..#.\n..#.\n.###"),
+        ('J', "..##\n...#\n...#\n...#\n#..#\n.##."),
+        ('K', "#..#\n#.#.\n##..\n#.#.\n#.#.\n#..#"),
+        ('L', "#...\n#...\n#...\n#...\n#...\n####"),
+        ('O', ".##.\n#..#\n#..#\n#..#\n#..#\n.##."),
+        ('P', "###.\n#..#\n#..#\n###.\n#...\n#..."),
+        ('R', "###.\n#..#\n#..#\n###.\n#.#.\n#..#"),
+        ('S', ".###\n#...\n#...\n.##.\n...#\n###."),
+        ('U', "#..#\n#..#\n#..#\n#..#\n#..#\n.##."),
+        ('Y', "#...#\n#...#\n.#.#.\n..#..\n..#..\n..#.."),
+        ('Z', "####\n...#\n..#.\n.#..\n#...\n####"),
+    ],
+};
+
+const LARGE_FONT: Font = Font {
+    height: 10,
+    pitch: 8,
+    glyphs: &[
+        (
+            'A',
+            "..##..\n.#..#.\n#....#\n#....#\n#....#\n######\n#....#\n#....#\n#....#\n#....#",
+        ),
+        (
+            'B',
+            "#####.\n#....#\n#....#\n#....#\n#####.\n#....#\n#....#\n#....#\n#....#\n#####.",
+        ),
+        (
+            'C',
+            ".####.\n#....#\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#....#\n.####.",
+        ),
+        (
+            'E',
+            "######\n#.....\n#.....\n#.....\n#####.\n#.....\n#.....\n#.....\n#.....\n######",
+        ),
+        (
+            'F',
+            "######\n#.....\n#.....\n#.....\n#####.\n#.....\n#.....\n#.....\n#.....\n#.....",
+        ),
+        (
+            'G',
+            ".####.\n#....#\n#.....\n#.....\n#.....\n#..###\n#....#\n#....#\n#...##\n.###.#",
+        ),
+        (
+            'H',
+            "#....#\n#....#\n#....#\n#....#\n######\n#....#\n#....#\n#....#\n#....#\n#....#",
+        ),
+        (
+            'J',
+            "...###\n....#.\n....#.\n....#.\n....#.\n....#.\n....#.\n#...#.\n#...#.\n.###..",
+        ),
+        (
+            'K',
+            "#....#\n#...#.\n#..#..\n#.#...\n##....\n##....\n#.#...\n#..#..\n#...#.\n#....#",
+        ),
+        (
+            'L',
+            "#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n######",
+        ),
+        (
+            'N',
+            "#....#\n##...#\n##...#\n#.#..#\n#.#..#\n#..#.#\n#..#.#\n#...##\n#...##\n#....#",
+        ),
+        (
+            'P',
+            "#####.\n#....#\n#....#\n#....#\n#####.\n#.....\n#.....\n#.....\n#.....\n#.....",
+        ),
+        (
+            'R',
+            "#####.\n#....#\n#....#\n#....#\n#####.\n#..#..\n#...#.\n#...#.\n#....#\n#....#",
+        ),
+        (
+            'X',
+            "#....#\n#....#\n.#..#.\n.#..#.\n..##..\n..##..\n.#..#.\n.#..#.\n#....#\n#....#",
+        ),
+        (
+            'Z',
+            "######\n.....#\n.....#\n....#.\n...#..\n..#...\n.#....\n#.....\n#.....\n######",
+        ),
+    ],
+};
+
+impl Font {
+    fn for_height(height: usize) -> Option<&'static Self> {
+        [&SMALL_FONT, &LARGE_FONT]
+            .into_iter()
+            .find(|font| font.height == height)
+    }
+
+    fn recognise_cell(&self, cell: &[Vec<bool>]) -> Option<char> {
+        self.glyphs
+            .iter()
+            .find(|(_, glyph)| {
+                glyph.lines().zip(cell).all(|(glyph_row, cell_row)| {
+                    // anything past the glyph's own width must be blank spacing
+                    let mut glyph_chars = glyph_row.chars();
+                    cell_row
+                        .iter()
+                        .all(|lit| *lit == (glyph_chars.next() == Some('#')))
+                })
+            })
+            .map(|(c, _)| *c)
+    }
+}
+
+/// Reads text drawn with `#` for lit pixels and anything else for unlit ones,
+/// as in the rendered output of many puzzles.
+pub fn recognise_str(art: &str) -> Option<String> {
+    let rows = art
+        .lines()
+        .map(|line| line.chars().map(|c| c == '#').collect())
+        .collect();
+    recognise(rows)
+}
+
+pub fn recognise_coords<T>(coords: &[Coord<T>]) -> Option<String>
+where
+    T: FromStr + PrimInt,
+    <T as FromStr>::Err: Debug,
+{
+    if coords.is_empty() {
+        return None;
+    }
+
+    let bounds = Bounds::calculate(coords);
+    let width: usize = num::cast(bounds.width).unwrap();
+    let height: usize = num::cast(bounds.height).unwrap();
+
+    let mut rows = vec![vec![false; width]; height];
+    for coord in coords {
+        let i: usize = bounds.coord_to_index(coord);
+        rows[i / width][i % width] = true;
+    }
+    recognise(rows)
+}
+
+pub fn recognise_grid<T, V, F>(grid: &Grid<T, V>, mut is_lit: F) -> Option<String>
+where
+    T: FromStr + PrimInt,
+    <T as FromStr>::Err: Debug,
+    F: FnMut(&Option<V>) -> bool,
+{
+    let rows = grid
+        .cells
+        .chunks(grid.width)
+        .map(|row| row.iter().map(&mut is_lit).collect())
+        .collect();
+    recognise(rows)
+}
+
+fn recognise(rows: Vec<Vec<bool>>) -> Option<String> {
+    let rows = trim(rows)?;
+    let font = Font::for_height(rows.len())?;
+
+    // glyphs like `I` don't start in their first column, so if the first
+    // letter is one of those the text is offset from the trimmed edge
+    (0..font.pitch).find_map(|offset| {
+        let width = rows[0].len() + offset;
+        let padded_width = width.div_ceil(font.pitch) * font.pitch;
+        let padded: Vec<Vec<bool>> = rows
+            .iter()
+            .map(|row| {
+                let mut out = vec![false; offset];
+                out.extend(row);
+                out.resize(padded_width, false);
+                out
+            })
+            .collect();
+
+        (0..padded_width / font.pitch)
+            .map(|i| {
+                let cell: Vec<Vec<bool>> = padded
+                    .iter()
+                    .map(|row| row[i * font.pitch..(i + 1) * font.pitch].to_vec())
+                    .collect();
+                font.recognise_cell(&cell)
+            })
+            .collect()
+    })
+}
+
+// crop to the smallest rectangle containing every lit pixel
+fn trim(rows: Vec<Vec<bool>>) -> Option<Vec<Vec<bool>>> {
+    let lit_rows: Vec<usize> = (0..rows.len())
+        .filter(|y| rows[*y].iter().any(|lit| *lit))
+        .collect();
+    let top = *lit_rows.first()?;
+    let bottom = *lit_rows.last()?;
+
+    let width = rows.iter().map(|row| row.len()).max().unwrap();
+    let lit_cols: Vec<usize> = (0..width)
+        .filter(|x| rows.iter().any(|row| row.get(*x) == Some(&true)))
+        .collect();
+    let left = *lit_cols.first()?;
+    let right = *lit_cols.last()?;
+
+    Some(
+        rows[top..=bottom]
+            .iter()
+            .map(|row| (left..=right).map(|x| row.get(x) == Some(&true)).collect())
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use indoc::indoc;
+
+    #[test]
+    fn test_recognise_small_font() {
+        let art = indoc! {"
+            ###..####.#..#.###..###..#....#..#.###..
+            #..#.#....#..#.#..#.#..#.#....#..#.#..#.
+            #..#.###..####.#..#.#..#.#....#..#.###..
+            ###..#....#..#.###..###..#....#..#.#..#.
+            #.#..#....#..#.#....#.#..#....#..#.#..#.
+            #..#.####.#..#.#....#..#.####..##..###..
+        "};
+        assert_eq!(recognise_str(art), Some("REHPRLUB".to_string()));
+
+        // letters without a blank column between them
+        let art = indoc! {"
+            .##....##.#...#####..##..
+            #..#....#.#...##....#..#.
+            #.......#..#.#.###..#..#.
+            #.##....#...#..#....####.
+            #..#.#..#...#..#....#..#.
+            .###..##....#..####.#..#.
+        "};
+        assert_eq!(recognise_str(art), Some("GJYEA".to_string()));
+    }
+
+    #[test]
+    fn test_recognise_offset_start() {
+        let art = indoc! {"
+            .###.#..#
+            ..#..#..#
+            ..#..####
+            ..#..#..#
+            ..#..#..#
+            .###.#..#
+        "};
+        assert_eq!(recognise_str(art), Some("IH".to_string()));
+    }
+
+    #[test]
+    fn test_recognise_large_font() {
+        let art = indoc! {"
+            ..##....#####....####...#....#.....###..#####...#....#..######
+            .#..#...#....#..#....#..#....#......#...#....#..#....#..#.....
+            #....#..#....#..#........#..#.......#...#....#...#..#...#.....
+            #....#..#....#..#........#..#.......#...#....#...#..#...#.....
+            #....#..#####...#.........##........#...#####.....##....#####.
+            ######..#....#..#..###....##........#...#....#....##....#.....
+            #....#..#....#..#....#...#..#.......#...#....#...#..#...#.....
+            #....#..#....#..#....#...#..#...#...#...#....#...#..#...#.....
+            #....#..#....#..#...##..#....#..#...#...#....#..#....#..#.....
+            #....#..#####....###.#..#....#...###....#####...#....#..#.....
+        "};
+        assert_eq!(recognise_str(art), Some("ABGXJBXF".to_string()));
+    }
+
+    const LZ: &str = indoc! {"
+        ..........
+        .#....####
+        .#.......#
+        .#......#.
+        .#.....#..
+        .#....#...
+        .####.####
+        ..........
+    "};
+
+    #[test]
+    fn test_recognise_coords() {
+        let grid: Grid<i32, ()> = Grid::parse(LZ, |c, _| (*c == '#').then_some(()));
+        let coords: Vec<Coord<i32>> = grid
+            .iter()
+            .map(|(i, _)| grid.index_to_coord(i) - Coord::new(20, 5))
+            .collect();
+        assert_eq!(recognise_coords(&coords), Some("LZ".to_string()));
+        assert_eq!(recognise_coords(&coords[1..]), None);
+    }
+
+    #[test]
+    fn test_recognise_grid() {
+        let grid: Grid<usize, bool> = Grid::parse(LZ, |c, _| Some(*c == '#'));
+        assert_eq!(
+            recognise_grid(&grid, |val| *val == Some(true)),
+            Some("LZ".to_string())
+        );
+    }
+}
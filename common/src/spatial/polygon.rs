@@ -0,0 +1,197 @@
+use num::{integer::gcd, PrimInt};
+
+use super::{Coord, Direction};
+
+/// A closed polygon on the integer lattice. Vertices are stored widened to
+/// `i128` so that areas of very large polygons can be computed exactly.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Polygon {
+    vertices: Vec<Coord<i128>>,
+}
+
+impl Polygon {
+    /// The last vertex is implicitly joined back to the first.
+    pub fn new<T: PrimInt>(vertices: &[Coord<T>]) -> Self {
+        let vertices = vertices
+            .iter()
+            .map(|c| Coord {
+                x: c.x.to_i128().unwrap(),
+                y: c.y.to_i128().unwrap(),
+            })
+            .collect();
+        Self { vertices }
+    }
+
+    /// Follows a path of moves starting at the origin, which must end up back
+    /// where it started.
+    pub fn from_moves<T, I>(moves: I) -> Self
+    where
+        T: PrimInt,
+        I: IntoIterator<Item = (Direction, T)>,
+    {
+        let origin = Coord { x: 0, y: 0 };
+        let mut vertices = vec![origin];
+        let mut curr = origin;
+
+        for (direction, length) in moves {
+            let length = length.to_i128().unwrap();
+            curr = match direction {
+                Direction::North => Coord::new(curr.x, curr.y - length),
+                Direction::South => Coord::new(curr.x, curr.y + length),
+                Direction::West => Coord::new(curr.x - length, curr.y),
+                Direction::East => Coord::new(curr.x + length, curr.y),
+            };
+            vertices.push(curr);
+        }
+
+        assert_eq!(curr, origin, "Path doesn't return to its start");
+        vertices.pop();
+
+        Self { vertices }
+    }
+
+    pub fn vertices(&self) -> &[Coord<i128>] {
+        &self.vertices
+    }
+
+    fn edges(&self) -> impl Iterator<Item = (&Coord<i128>, &Coord<i128>)> {
+        self.vertices
+            .iter()
+            .zip(self.vertices.iter().cycle().skip(1))
+    }
+
+    /// Twice the signed area, via the shoelace formula. Positive when the
+    /// vertices run clockwise on screen (with y increasing downwards).
+    pub fn double_signed_area(&self) -> i128 {
+        self.edges().map(|(a, b)| a.x * b.y - b.x * a.y).sum()
+    }
+
+    /// Area enclosed by the vertices. Always exact for rectilinear polygons;
+    /// for others, half units are rounded down.
+    pub fn area(&self) -> i128 {
+        self.double_signed_area().abs() / 2
+    }
+
+    /// Number of lattice points lying on the edges.
+    pub fn boundary_points(&self) -> i128 {
+        self.edges()
+            .map(|(a, b)| gcd((b.x - a.x).abs(), (b.y - a.y).abs()))
+            .sum()
+    }
+
+    /// Number of lattice points strictly inside, by Pick's theorem.
+    pub fn interior_points(&self) -> i128 {
+        (self.double_signed_area().abs() - self.boundary_points() + 2) / 2
+    }
+
+    /// Number of lattice points inside or on the boundary, ie. the number of
+    /// grid cells covered if the path is traced through cell centres.
+    pub fn enclosed_points(&self) -> i128 {
+        self.interior_points() + self.boundary_points()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use indoc::indoc;
+
+    const DIG_PLAN: &str = indoc! {"
+        R 6 (#70c710)
+        D 5 (#0dc571)
+        L 2 (#5713f0)
+        D 2 (#d2c081)
+        R 2 (#59c680)
+        D 2 (#411b91)
+        L 5 (#8ceee2)
+        U 2 (#caa173)
+        L 1 (#1b58a2)
+        U 2 (#caa171)
+        R 2 (#7807d2)
+        U 3 (#a77fa3)
+        L 2 (#015232)
+        U 2 (#7a21e3)
+    "};
+
+    fn parse_direction(c: char) -> Direction {
+        match c {
+            'U' | '3' => Direction::North,
+            'D' | '1' => Direction::South,
+            'L' | '2' => Direction::West,
+            'R' | '0' => Direction::East,
+            _ => panic!("Unexpected direction: {:?}", c),
+        }
+    }
+
+    #[test]
+    fn test_dig_plan() {
+        let moves = DIG_PLAN.lines().map(|line| {
+            let mut parts = line.split(' ');
+            let direction = parse_direction(parts.next().unwrap().chars().next().unwrap());
+            let length = parts.next().unwrap().parse::<u8>().unwrap();
+            (direction, length)
+        });
+        let polygon = Polygon::from_moves(moves);
+        assert_eq!(polygon.boundary_points(), 38);
+        assert_eq!(polygon.enclosed_points(), 62);
+    }
+
+    #[test]
+    fn test_dig_plan_large() {
+        let moves = DIG_PLAN.lines().map(|line| {
+            let hex = &line[line.find('#').unwrap() + 1..line.len() - 1];
+            let length = u64::from_str_radix(&hex[0..5], 16).unwrap();
+            let direction = parse_direction(hex.chars().nth(5).unwrap());
+            (direction, length)
+        });
+        let polygon = Polygon::from_moves(moves);
+        assert_eq!(polygon.enclosed_points(), 952408144115);
+    }
+
+    #[test]
+    fn test_area() {
+        let square = Polygon::new(&[
+            Coord::new(0u8, 0),
+            Coord::new(4, 0),
+            Coord::new(4, 4),
+            Coord::new(0, 4),
+        ]);
+        assert_eq!(square.double_signed_area(), 32);
+        assert_eq!(square.area(), 16);
+        assert_eq!(square.boundary_points(), 16);
+        assert_eq!(square.interior_points(), 9);
+
+        let triangle = Polygon::new(&[Coord::new(0i32, 0), Coord::new(0, 3), Coord::new(3, 0)]);
+        assert_eq!(triangle.double_signed_area(), -9);
+        assert_eq!(triangle.area(), 4);
+        assert_eq!(triangle.boundary_points(), 9);
+        assert_eq!(triangle.interior_points(), 1);
+    }
+
+    #[test]
+    fn test_enclosed_tiles() {
+        // the pipe loop from the pipe maze example, traced through tile centres
+        let polygon = Polygon::new(&[
+            Coord::new(1, 1),
+            Coord::new(9, 1),
+            Coord::new(9, 7),
+            Coord::new(6, 7),
+            Coord::new(6, 5),
+            Coord::new(8, 5),
+            Coord::new(8, 2),
+            Coord::new(2, 2),
+            Coord::new(2, 5),
+            Coord::new(4, 5),
+            Coord::new(4, 7),
+            Coord::new(1, 7),
+        ]);
+        assert_eq!(polygon.interior_points(), 4);
+    }
+
+    #[test]
+    #[should_panic(expected = "Path doesn't return to its start")]
+    fn test_unclosed_moves() {
+        Polygon::from_moves([(Direction::East, 2), (Direction::South, 2)]);
+    }
+}
@@ -0,0 +1,218 @@
+use std::{fmt::Debug, fmt::Write, str::FromStr};
+
+use num::PrimInt;
+
+use super::Grid;
+use crate::file;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Rgb {
+    pub const BLACK: Self = Self::new(0, 0, 0);
+    pub const WHITE: Self = Self::new(255, 255, 255);
+
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+
+    pub fn grey(level: u8) -> Self {
+        Self::new(level, level, level)
+    }
+
+    /// Closest entry in the xterm 256-colour palette, picking from the 6x6x6
+    /// colour cube or the 24-step greyscale ramp.
+    pub fn to_ansi256(&self) -> u8 {
+        const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+        let nearest_level = |v: u8| {
+            (0..6)
+                .min_by_key(|i| (CUBE_LEVELS[*i] as i32 - v as i32).abs())
+                .unwrap()
+        };
+        let (r, g, b) = (
+            nearest_level(self.r),
+            nearest_level(self.g),
+            nearest_level(self.b),
+        );
+        let cube = Self::new(CUBE_LEVELS[r], CUBE_LEVELS[g], CUBE_LEVELS[b]);
+        let cube_index = 16 + 36 * r + 6 * g + b;
+
+        let average = (self.r as usize + self.g as usize + self.b as usize) / 3;
+        let grey_step = (average.saturating_sub(3) / 10).min(23);
+        let grey = Self::grey(8 + 10 * grey_step as u8);
+        let grey_index = 232 + grey_step;
+
+        if self.distance_squared(&grey) < self.distance_squared(&cube) {
+            grey_index as u8
+        } else {
+            cube_index as u8
+        }
+    }
+
+    fn distance_squared(&self, other: &Self) -> i32 {
+        let dr = self.r as i32 - other.r as i32;
+        let dg = self.g as i32 - other.g as i32;
+        let db = self.b as i32 - other.b as i32;
+        dr * dr + dg * dg + db * db
+    }
+}
+
+impl<T, V> Grid<T, V>
+where
+    T: FromStr + PrimInt,
+    <T as FromStr>::Err: Debug,
+{
+    fn rows(&self) -> impl Iterator<Item = &[Option<V>]> {
+        self.cells.chunks(self.width)
+    }
+
+    /// One line per row, each terminated by a newline.
+    pub fn render<F>(&self, mut to_char: F) -> String
+    where
+        F: FnMut(&Option<V>) -> char,
+    {
+        let mut out = String::with_capacity((self.width + 1) * self.height);
+        for row in self.rows() {
+            out.extend(row.iter().map(&mut to_char));
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Each cell is drawn as two spaces on an ANSI 256-colour background, so
+    /// that cells come out roughly square in most terminals.
+    pub fn render_ansi<F>(&self, mut to_colour: F) -> String
+    where
+        F: FnMut(&Option<V>) -> Rgb,
+    {
+        let mut out = String::new();
+        for row in self.rows() {
+            let mut last = None;
+            for val in row {
+                let colour = to_colour(val).to_ansi256();
+                if last != Some(colour) {
+                    write!(out, "\x1b[48;5;{}m", colour).unwrap();
+                    last = Some(colour);
+                }
+                out.push_str("  ");
+            }
+            out.push_str("\x1b[0m\n");
+        }
+        out
+    }
+
+    /// Binary PBM (P4) image, with cells where `is_black` holds drawn black.
+    pub fn to_pbm<F>(&self, mut is_black: F) -> Vec<u8>
+    where
+        F: FnMut(&Option<V>) -> bool,
+    {
+        let mut out = format!("P4\n{} {}\n", self.width, self.height).into_bytes();
+        for row in self.rows() {
+            // each row is packed into whole bytes, most significant bit first
+            for chunk in row.chunks(8) {
+                let byte = chunk
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, val)| is_black(val))
+                    .fold(0u8, |acc, (i, _)| acc | (0b1000_0000 >> i));
+                out.push(byte);
+            }
+        }
+        out
+    }
+
+    /// Binary PPM (P6) image, with 8 bits per channel.
+    pub fn to_ppm<F>(&self, mut to_colour: F) -> Vec<u8>
+    where
+        F: FnMut(&Option<V>) -> Rgb,
+    {
+        let mut out = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        for val in &self.cells {
+            let colour = to_colour(val);
+            out.extend([colour.r, colour.g, colour.b]);
+        }
+        out
+    }
+
+    pub fn write_pbm<F>(&self, path: &str, is_black: F)
+    where
+        F: FnMut(&Option<V>) -> bool,
+    {
+        file::write_file(path, &self.to_pbm(is_black));
+    }
+
+    pub fn write_ppm<F>(&self, path: &str, to_colour: F)
+    where
+        F: FnMut(&Option<V>) -> Rgb,
+    {
+        file::write_file(path, &self.to_ppm(to_colour));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use indoc::indoc;
+
+    const EXAMPLE: &str = indoc! {"
+        #..#......#
+        .##.......#
+    "};
+
+    fn parse(input: &str) -> Grid<usize, ()> {
+        Grid::parse(input, |c, _| (*c == '#').then_some(()))
+    }
+
+    #[test]
+    fn test_render() {
+        let grid = parse(EXAMPLE);
+        let out = grid.render(|val| if val.is_some() { '#' } else { '.' });
+        assert_eq!(out, EXAMPLE);
+    }
+
+    #[test]
+    fn test_to_pbm() {
+        let grid = parse(EXAMPLE);
+        let out = grid.to_pbm(|val| val.is_some());
+        let mut expected = b"P4\n11 2\n".to_vec();
+        expected.extend([0b1001_0000, 0b0010_0000, 0b0110_0000, 0b0010_0000]);
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn test_to_ppm() {
+        let grid = parse("#.\n");
+        let out = grid.to_ppm(|val| match val {
+            Some(()) => Rgb::new(255, 0, 0),
+            None => Rgb::grey(128),
+        });
+        let mut expected = b"P6\n2 1\n255\n".to_vec();
+        expected.extend([255, 0, 0, 128, 128, 128]);
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn test_render_ansi() {
+        let grid = parse("#..\n");
+        let out = grid.render_ansi(|val| match val {
+            Some(()) => Rgb::WHITE,
+            None => Rgb::BLACK,
+        });
+        assert_eq!(out, "\x1b[48;5;231m  \x1b[48;5;16m    \x1b[0m\n");
+    }
+
+    #[test]
+    fn test_to_ansi256() {
+        assert_eq!(Rgb::BLACK.to_ansi256(), 16);
+        assert_eq!(Rgb::WHITE.to_ansi256(), 231);
+        assert_eq!(Rgb::new(255, 0, 0).to_ansi256(), 196);
+        assert_eq!(Rgb::new(0, 135, 255).to_ansi256(), 33);
+        assert_eq!(Rgb::grey(128).to_ansi256(), 244);
+    }
+}
@@ -0,0 +1,313 @@
+use std::{collections::HashMap, ops::Mul};
+
+pub type Point3 = [i64; 3];
+
+/// One of the 24 ways to turn a cube in place, as a matrix of 0s and ±1s
+/// with determinant 1.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Rotation {
+    matrix: [[i64; 3]; 3],
+}
+
+impl Rotation {
+    pub const IDENTITY: Self = Self {
+        matrix: [[1, 0, 0], [0, 1, 0], [0, 0, 1]],
+    };
+
+    /// Every proper rotation, starting with the identity.
+    pub fn all() -> Vec<Self> {
+        let permutations = [
+            [0, 1, 2],
+            [0, 2, 1],
+            [1, 0, 2],
+            [1, 2, 0],
+            [2, 0, 1],
+            [2, 1, 0],
+        ];
+
+        let mut out = vec![];
+        for axes in permutations {
+            for signs in 0..8 {
+                let mut matrix = [[0; 3]; 3];
+                for (row, axis) in axes.iter().enumerate() {
+                    matrix[row][*axis] = if signs & (1 << row) == 0 { 1 } else { -1 };
+                }
+                let rotation = Self { matrix };
+                // the other half are reflections
+                if rotation.determinant() == 1 {
+                    out.push(rotation);
+                }
+            }
+        }
+        out
+    }
+
+    fn determinant(&self) -> i64 {
+        let m = &self.matrix;
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    }
+
+    pub fn matrix(&self) -> &[[i64; 3]; 3] {
+        &self.matrix
+    }
+
+    pub fn apply(&self, point: &Point3) -> Point3 {
+        self.matrix
+            .map(|row| row[0] * point[0] + row[1] * point[1] + row[2] * point[2])
+    }
+
+    /// Rotates by `other` first, then by `self`.
+    pub fn compose(&self, other: &Self) -> Self {
+        let mut matrix = [[0; 3]; 3];
+        for (row, out) in matrix.iter_mut().enumerate() {
+            for (col, val) in out.iter_mut().enumerate() {
+                *val = (0..3)
+                    .map(|k| self.matrix[row][k] * other.matrix[k][col])
+                    .sum();
+            }
+        }
+        Self { matrix }
+    }
+
+    pub fn inverse(&self) -> Self {
+        // rotation matrices are orthogonal, so this is just the transpose
+        let mut matrix = [[0; 3]; 3];
+        for (row, out) in matrix.iter_mut().enumerate() {
+            for (col, val) in out.iter_mut().enumerate() {
+                *val = self.matrix[col][row];
+            }
+        }
+        Self { matrix }
+    }
+}
+
+impl Mul for Rotation {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        self.compose(&rhs)
+    }
+}
+
+/// A rotation about the origin followed by a translation.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Transform {
+    pub rotation: Rotation,
+    pub translation: Point3,
+}
+
+impl Transform {
+    pub const IDENTITY: Self = Self {
+        rotation: Rotation::IDENTITY,
+        translation: [0, 0, 0],
+    };
+
+    pub fn apply(&self, point: &Point3) -> Point3 {
+        let rotated = self.rotation.apply(point);
+        [0, 1, 2].map(|i| rotated[i] + self.translation[i])
+    }
+
+    /// Applies `other` first, then `self`.
+    pub fn compose(&self, other: &Self) -> Self {
+        Self {
+            rotation: self.rotation.compose(&other.rotation),
+            translation: self.apply(&other.translation),
+        }
+    }
+
+    pub fn inverse(&self) -> Self {
+        let rotation = self.rotation.inverse();
+        let moved = rotation.apply(&self.translation);
+        Self {
+            rotation,
+            translation: moved.map(|v| -v),
+        }
+    }
+}
+
+/// Finds a transform taking at least `min_overlap` points of `cloud` exactly
+/// onto points of `reference`, trying every rotation. Points in each cloud
+/// must be distinct.
+pub fn align(reference: &[Point3], cloud: &[Point3], min_overlap: usize) -> Option<Transform> {
+    for rotation in Rotation::all() {
+        let rotated: Vec<Point3> = cloud.iter().map(|p| rotation.apply(p)).collect();
+
+        // every pairing votes for the translation that would line it up
+        let mut votes: HashMap<Point3, usize> = HashMap::new();
+        for r in reference {
+            for p in &rotated {
+                let translation = [0, 1, 2].map(|i| r[i] - p[i]);
+                let count = votes.entry(translation).or_insert(0);
+                *count += 1;
+                if *count >= min_overlap {
+                    return Some(Transform {
+                        rotation,
+                        translation,
+                    });
+                }
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashSet;
+
+    use indoc::indoc;
+
+    #[test]
+    fn test_group() {
+        let all = Rotation::all();
+        assert_eq!(all.len(), 24);
+        assert_eq!(all[0], Rotation::IDENTITY);
+        let unique: HashSet<&Rotation> = all.iter().collect();
+        assert_eq!(unique.len(), 24);
+
+        for a in &all {
+            assert_eq!(a.compose(&a.inverse()), Rotation::IDENTITY);
+            for b in &all {
+                assert!(unique.contains(&(*a * *b)));
+            }
+        }
+
+        // a quarter turn about z, four times over
+        let quarter = Rotation {
+            matrix: [[0, -1, 0], [1, 0, 0], [0, 0, 1]],
+        };
+        assert_eq!(quarter.apply(&[1, 2, 3]), [-2, 1, 3]);
+        assert_eq!(quarter * quarter * quarter * quarter, Rotation::IDENTITY);
+    }
+
+    #[test]
+    fn test_transform() {
+        let all = Rotation::all();
+        let a = Transform {
+            rotation: all[5],
+            translation: [1, -2, 3],
+        };
+        let b = Transform {
+            rotation: all[17],
+            translation: [-7, 0, 4],
+        };
+        let p = [3, 5, -8];
+
+        assert_eq!(a.inverse().apply(&a.apply(&p)), p);
+        assert_eq!(a.compose(&b).apply(&p), a.apply(&b.apply(&p)));
+        assert_eq!(a.compose(&a.inverse()), Transform::IDENTITY);
+    }
+
+    const SCANNERS: &str = indoc! {"
+        --- scanner 0 ---
+        404,-588,-901
+        528,-643,409
+        -838,591,734
+        390,-675,-793
+        -537,-823,-458
+        -485,-357,347
+        -345,-311,381
+        -661,-816,-575
+        -876,649,763
+        -618,-824,-621
+        553,345,-567
+        474,580,667
+        -447,-329,318
+        -584,868,-557
+        544,-627,-890
+        564,392,-477
+        455,729,728
+        -892,524,684
+        -689,845,-530
+        423,-701,434
+        7,-33,-71
+        630,319,-379
+        443,580,662
+        -789,900,-551
+        459,-707,401
+
+        --- scanner 1 ---
+        686,422,578
+        605,423,415
+        515,917,-361
+        -336,658,858
+        95,138,22
+        -476,619,847
+        -340,-569,-846
+        567,-361,727
+        -460,603,-452
+        669,-402,600
+        729,430,532
+        -500,-761,534
+        -322,571,750
+        -466,-666,-811
+        -429,-592,574
+        -355,545,-477
+        703,-491,-529
+        -328,-685,520
+        413,935,-424
+        -391,539,-444
+        586,-435,557
+        -364,-763,-893
+        807,-499,-711
+        755,-354,-619
+        553,889,-390
+    "};
+
+    fn parse_scanners(input: &str) -> Vec<Vec<Point3>> {
+        input
+            .split("\n\n")
+            .map(|scanner| {
+                scanner
+                    .lines()
+                    .skip(1)
+                    .map(|line| {
+                        let nums: Vec<i64> = line.split(',').map(|n| n.parse().unwrap()).collect();
+                        [nums[0], nums[1], nums[2]]
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_align_scanners() {
+        let scanners = parse_scanners(SCANNERS);
+        let transform = align(&scanners[0], &scanners[1], 12).unwrap();
+        // where scanner 1 sits, relative to scanner 0
+        assert_eq!(transform.translation, [68, -1246, -43]);
+
+        let shared: HashSet<Point3> = scanners[1]
+            .iter()
+            .map(|p| transform.apply(p))
+            .filter(|p| scanners[0].contains(p))
+            .collect();
+        assert_eq!(shared.len(), 12);
+        assert!(shared.contains(&[-618, -824, -621]));
+
+        assert_eq!(align(&scanners[0], &scanners[1], 13), None);
+    }
+
+    #[test]
+    fn test_align_any_cloud() {
+        let cloud: Vec<Point3> = (0..10)
+            .map(|i| [i * i, 3 * i - 7, 11 - i * i * i])
+            .collect();
+        for rotation in Rotation::all() {
+            let expected = Transform {
+                rotation,
+                translation: [100, -50, 25],
+            };
+            let mut moved: Vec<Point3> = cloud.iter().map(|p| expected.apply(p)).collect();
+            moved.push([1000, 1000, 1000]);
+
+            let found = align(&moved, &cloud, 10).unwrap();
+            assert_eq!(found, expected);
+            assert_eq!(found.inverse().apply(&moved[3]), cloud[3]);
+        }
+    }
+}
@@ -0,0 +1,266 @@
+use std::cmp::Ordering;
+
+use num::{PrimInt, Zero};
+
+use super::Coord;
+use crate::math::Rational;
+
+/// A straight line segment between two lattice points, inclusive of both ends.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Segment<T> {
+    pub start: Coord<T>,
+    pub end: Coord<T>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Intersection {
+    Point(Coord<Rational>),
+    // collinear segments sharing more than a single point
+    Overlap(Coord<Rational>, Coord<Rational>),
+}
+
+impl<T> Segment<T>
+where
+    T: PrimInt,
+{
+    pub fn new(start: Coord<T>, end: Coord<T>) -> Self {
+        Self { start, end }
+    }
+
+    pub fn is_horizontal(&self) -> bool {
+        self.start.y == self.end.y
+    }
+
+    pub fn is_vertical(&self) -> bool {
+        self.start.x == self.end.x
+    }
+
+    pub fn is_diagonal(&self) -> bool {
+        let (dx, dy) = self.delta();
+        dx != 0 && dx.abs() == dy.abs()
+    }
+
+    fn widen(coord: &Coord<T>) -> (i128, i128) {
+        (coord.x.to_i128().unwrap(), coord.y.to_i128().unwrap())
+    }
+
+    fn narrow(x: i128, y: i128) -> Coord<T> {
+        Coord {
+            x: T::from(x).unwrap(),
+            y: T::from(y).unwrap(),
+        }
+    }
+
+    fn delta(&self) -> (i128, i128) {
+        let (x0, y0) = Self::widen(&self.start);
+        let (x1, y1) = Self::widen(&self.end);
+        (x1 - x0, y1 - y0)
+    }
+
+    /// Lattice points along the segment from start to end, using Bresenham's
+    /// algorithm. Horizontal, vertical and 45° segments hit every point
+    /// exactly.
+    pub fn points(&self) -> impl Iterator<Item = Coord<T>> {
+        let (mut x, mut y) = Self::widen(&self.start);
+        let (dx, dy) = self.delta();
+        let (sx, sy) = (dx.signum(), dy.signum());
+        let (dx, dy) = (dx.abs(), -dy.abs());
+        let mut err = dx + dy;
+
+        (0..=dx.max(-dy)).map(move |_| {
+            let out = Self::narrow(x, y);
+            let e2 = 2 * err;
+            if e2 >= dy {
+                err += dy;
+                x += sx;
+            }
+            if e2 <= dx {
+                err += dx;
+                y += sy;
+            }
+            out
+        })
+    }
+
+    pub fn intersects(&self, other: &Self) -> bool {
+        self.intersection(other).is_some()
+    }
+
+    /// Exact intersection of two segments, with coordinates as rationals so
+    /// that crossings between lattice points don't need any tolerance.
+    pub fn intersection(&self, other: &Self) -> Option<Intersection> {
+        let p = Self::widen(&self.start);
+        let r = self.delta();
+        let q = Self::widen(&other.start);
+        let s = other.delta();
+
+        let qp = (q.0 - p.0, q.1 - p.1);
+        let denom = cross(r, s);
+
+        if denom != 0 {
+            // lines cross at a single point: p + t*r == q + u*s
+            let t = Rational::new(cross(qp, s), denom);
+            let u = Rational::new(cross(qp, r), denom);
+            if in_unit_range(&t) && in_unit_range(&u) {
+                Some(Intersection::Point(along(p, r, t)))
+            } else {
+                None
+            }
+        } else if cross(qp, r) != 0 || cross(qp, s) != 0 {
+            // parallel but not on the same line
+            None
+        } else {
+            self.collinear_intersection(p, r, q, s)
+        }
+    }
+
+    fn collinear_intersection(
+        &self,
+        p: (i128, i128),
+        r: (i128, i128),
+        q: (i128, i128),
+        s: (i128, i128),
+    ) -> Option<Intersection> {
+        let rr = dot(r, r);
+        if rr == 0 {
+            // this segment is a single point
+            let ss = dot(s, s);
+            let on_other = if ss == 0 {
+                p == q
+            } else {
+                in_unit_range(&Rational::new(dot((p.0 - q.0, p.1 - q.1), s), ss))
+            };
+            return on_other.then(|| Intersection::Point(along(p, r, Rational::zero())));
+        }
+
+        // project the other segment's ends onto this one, as multiples of r
+        let t0 = Rational::new(dot((q.0 - p.0, q.1 - p.1), r), rr);
+        let t1 = t0 + Rational::new(dot(s, r), rr);
+        let (lo, hi) = if t0 <= t1 { (t0, t1) } else { (t1, t0) };
+        let lo = lo.max(Rational::zero());
+        let hi = hi.min(Rational::from_integer(1));
+
+        match lo.cmp(&hi) {
+            Ordering::Greater => None,
+            Ordering::Equal => Some(Intersection::Point(along(p, r, lo))),
+            Ordering::Less => Some(Intersection::Overlap(along(p, r, lo), along(p, r, hi))),
+        }
+    }
+}
+
+fn cross(a: (i128, i128), b: (i128, i128)) -> i128 {
+    a.0 * b.1 - a.1 * b.0
+}
+
+fn dot(a: (i128, i128), b: (i128, i128)) -> i128 {
+    a.0 * b.0 + a.1 * b.1
+}
+
+fn in_unit_range(t: &Rational) -> bool {
+    *t >= Rational::zero() && *t <= Rational::from_integer(1)
+}
+
+fn along(p: (i128, i128), r: (i128, i128), t: Rational) -> Coord<Rational> {
+    Coord {
+        x: Rational::from_integer(p.0) + t * r.0,
+        y: Rational::from_integer(p.1) + t * r.1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(x0: i32, y0: i32, x1: i32, y1: i32) -> Segment<i32> {
+        Segment::new(Coord::new(x0, y0), Coord::new(x1, y1))
+    }
+
+    fn point(x: i128, y: i128) -> Coord<Rational> {
+        Coord {
+            x: Rational::from_integer(x),
+            y: Rational::from_integer(y),
+        }
+    }
+
+    fn coords(points: &[(i32, i32)]) -> Vec<Coord<i32>> {
+        points.iter().map(|(x, y)| Coord::new(*x, *y)).collect()
+    }
+
+    #[test]
+    fn test_points() {
+        let horizontal = segment(3, 4, 1, 4);
+        assert!(horizontal.is_horizontal());
+        assert_eq!(
+            horizontal.points().collect::<Vec<_>>(),
+            coords(&[(3, 4), (2, 4), (1, 4)])
+        );
+
+        let diagonal = segment(9, 7, 7, 9);
+        assert!(diagonal.is_diagonal());
+        assert_eq!(
+            diagonal.points().collect::<Vec<_>>(),
+            coords(&[(9, 7), (8, 8), (7, 9)])
+        );
+
+        let steep = segment(0, 0, 2, 5);
+        assert!(!steep.is_diagonal() && !steep.is_vertical());
+        assert_eq!(
+            steep.points().collect::<Vec<_>>(),
+            coords(&[(0, 0), (0, 1), (1, 2), (1, 3), (2, 4), (2, 5)])
+        );
+
+        let single = segment(2, 2, 2, 2);
+        assert_eq!(single.points().collect::<Vec<_>>(), coords(&[(2, 2)]));
+
+        // unsigned coordinates can still run backwards
+        let unsigned: Segment<u8> = Segment::new(Coord::new(2, 0), Coord::new(0, 2));
+        assert_eq!(unsigned.points().count(), 3);
+    }
+
+    #[test]
+    fn test_crossing() {
+        let a = segment(0, 0, 4, 4);
+        let b = segment(0, 4, 4, 0);
+        assert_eq!(a.intersection(&b), Some(Intersection::Point(point(2, 2))));
+
+        // crossing between lattice points
+        let c = segment(0, 0, 1, 1);
+        let d = segment(0, 1, 1, 0);
+        let half = Rational::new(1, 2);
+        assert_eq!(
+            c.intersection(&d),
+            Some(Intersection::Point(Coord { x: half, y: half }))
+        );
+
+        // touching at an end
+        let e = segment(4, 4, 6, 0);
+        assert_eq!(a.intersection(&e), Some(Intersection::Point(point(4, 4))));
+
+        // lines cross, but past the end of one segment
+        let f = segment(0, 10, 10, 9);
+        assert!(!a.intersects(&f));
+    }
+
+    #[test]
+    fn test_parallel() {
+        let a = segment(0, 0, 4, 0);
+        assert!(!a.intersects(&segment(0, 1, 4, 1)));
+        assert!(!a.intersects(&segment(5, 0, 7, 0)));
+        assert_eq!(
+            a.intersection(&segment(6, 0, 2, 0)),
+            Some(Intersection::Overlap(point(2, 0), point(4, 0)))
+        );
+        assert_eq!(
+            a.intersection(&segment(4, 0, 8, 0)),
+            Some(Intersection::Point(point(4, 0)))
+        );
+        assert_eq!(
+            a.intersection(&segment(3, 0, 3, 0)),
+            Some(Intersection::Point(point(3, 0)))
+        );
+        assert_eq!(
+            segment(3, 0, 3, 0).intersection(&a),
+            Some(Intersection::Point(point(3, 0)))
+        );
+    }
+}
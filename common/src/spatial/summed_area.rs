@@ -0,0 +1,160 @@
+use std::{fmt::Debug, str::FromStr};
+
+use num::PrimInt;
+
+use super::{Bounds, Coord, Grid};
+
+/// Running totals over a grid, so the sum over any rectangle takes just four
+/// lookups however big it is.
+#[derive(Clone, Debug)]
+pub struct SummedAreaTable<T> {
+    bounds: Bounds<T>,
+    width: usize,
+    height: usize,
+    // sum of everything above and left of each cell, with an extra zero row
+    // and column at the start
+    sums: Vec<i64>,
+}
+
+impl<T> SummedAreaTable<T>
+where
+    T: FromStr + PrimInt,
+    <T as FromStr>::Err: Debug,
+{
+    fn at(&self, x: usize, y: usize) -> i64 {
+        self.sums[y * (self.width + 1) + x]
+    }
+
+    // offsets into the table for the rectangle starting at (x, y)
+    fn window_sum(&self, x: usize, y: usize, width: usize, height: usize) -> i64 {
+        self.at(x + width, y + height) - self.at(x, y + height) - self.at(x + width, y)
+            + self.at(x, y)
+    }
+
+    pub fn total(&self) -> i64 {
+        self.at(self.width, self.height)
+    }
+
+    /// Sum over a rectangle, which must lie within the grid.
+    pub fn sum(&self, rect: &Bounds<T>) -> i64 {
+        assert!(
+            self.bounds.contains_bounds(rect),
+            "Rectangle is off the grid"
+        );
+        let top_left: usize = self.bounds.coord_to_index(&Coord::new(rect.left, rect.top));
+        let x = top_left % self.width;
+        let y = top_left / self.width;
+        self.window_sum(x, y, cast!(rect.width), cast!(rect.height))
+    }
+
+    /// The `size`×`size` square with the largest sum, and that sum. Ties go
+    /// to whichever comes first in row-major order.
+    pub fn best_window(&self, size: usize) -> Option<(Bounds<T>, i64)> {
+        if size == 0 || size > self.width || size > self.height {
+            return None;
+        }
+
+        let mut best: Option<(usize, usize, i64)> = None;
+        for y in 0..=self.height - size {
+            for x in 0..=self.width - size {
+                let sum = self.window_sum(x, y, size, size);
+                match best {
+                    Some((_, _, b)) if sum <= b => (),
+                    _ => best = Some((x, y, sum)),
+                }
+            }
+        }
+
+        best.map(|(x, y, sum)| {
+            let top_left = self.bounds.index_to_coord(y * self.width + x);
+            let last: T = cast!(size - 1);
+            let window = Bounds::new(top_left.x, top_left.x + last, top_left.y, top_left.y + last);
+            (window, sum)
+        })
+    }
+}
+
+impl<T, V> Grid<T, V>
+where
+    T: FromStr + PrimInt,
+    <T as FromStr>::Err: Debug,
+{
+    /// Totals up `value` for every cell, including empty ones.
+    pub fn summed_area_table<F>(&self, mut value: F) -> SummedAreaTable<T>
+    where
+        F: FnMut(usize, &Option<V>) -> i64,
+    {
+        let stride = self.width + 1;
+        let mut sums = vec![0; stride * (self.height + 1)];
+        for y in 0..self.height {
+            let mut row = 0;
+            for x in 0..self.width {
+                let i = y * self.width + x;
+                row += value(i, &self.cells[i]);
+                sums[(y + 1) * stride + x + 1] = sums[y * stride + x + 1] + row;
+            }
+        }
+
+        SummedAreaTable {
+            bounds: self.bounds,
+            width: self.width,
+            height: self.height,
+            sums,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use indoc::indoc;
+
+    const EXAMPLE: &str = indoc! {"
+        31415
+        92653
+        58979
+        32384
+    "};
+
+    fn table() -> SummedAreaTable<i32> {
+        let grid: Grid<i32, u32> = Grid::parse(EXAMPLE, |c, _| c.to_digit(10));
+        grid.summed_area_table(|_, v| v.unwrap() as i64)
+    }
+
+    #[test]
+    fn test_sums() {
+        let table = table();
+        assert_eq!(table.total(), 97);
+        assert_eq!(table.sum(&Bounds::new(0, 0, 0, 0)), 3);
+        assert_eq!(table.sum(&Bounds::new(1, 2, 1, 2)), 2 + 6 + 8 + 9);
+        assert_eq!(table.sum(&Bounds::new(4, 4, 0, 3)), 5 + 3 + 9 + 4);
+        assert_eq!(table.sum(&Bounds::new(0, 4, 3, 3)), 20);
+    }
+
+    #[test]
+    fn test_best_window() {
+        let table = table();
+        assert_eq!(table.best_window(1), Some((Bounds::new(0, 0, 1, 1), 9)));
+        assert_eq!(table.best_window(2), Some((Bounds::new(3, 4, 2, 3), 28)));
+        assert_eq!(table.best_window(4), Some((Bounds::new(1, 4, 0, 3), 77)));
+        assert_eq!(table.best_window(5), None);
+    }
+
+    #[test]
+    fn test_offset_bounds() {
+        let mut grid: Grid<i32, i64> = Grid::new(Bounds::new(-5, 5, 10, 12));
+        let i = grid.coord_to_index(&Coord::new(-1, 11));
+        grid.set(i, 7);
+        let table = grid.summed_area_table(|_, v| v.unwrap_or(-1));
+        assert_eq!(table.total(), 7 - 32);
+        assert_eq!(table.sum(&Bounds::new(-1, 0, 11, 12)), 7 - 3);
+        assert_eq!(table.best_window(1), Some((Bounds::new(-1, -1, 11, 11), 7)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Rectangle is off the grid")]
+    fn test_off_grid() {
+        table().sum(&Bounds::new(3, 5, 0, 0));
+    }
+}
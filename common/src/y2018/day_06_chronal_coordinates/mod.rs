@@ -1,5 +1,7 @@
-use crate::{interface::AoCWithParams, spatial::*};
-use std::collections::{HashMap, HashSet};
+use crate::{
+    interface::AoCWithParams,
+    spatial::{kd_tree::*, *},
+};
 
 type NumT = u16;
 type BoundsT = Bounds<NumT>;
@@ -19,120 +21,43 @@ impl AoCWithParams<(Vec<CoordT>, BoundsT), (), usize, usize, usize> for Day {
     }
 
     fn part1((nodes, bounds): &(Vec<CoordT>, BoundsT), _: ()) -> usize {
-        let mut infinite: HashSet<usize> = HashSet::new();
-        let mut map: Grid<NumT, Territory> = Grid::new(*bounds);
-        let node_indices: Vec<usize> = nodes.iter().map(|c| map.coord_to_index(c)).collect();
-
-        for i in &node_indices {
-            map.set(*i, Territory::Node(*i));
-        }
-
-        // explore from the nodes
-        let mut frontier: Vec<(usize, usize)> = node_indices.iter().map(|i| (*i, *i)).collect();
-        while !frontier.is_empty() {
-            // for each round of exploration, expand to neighbours, tracking
-            // which nodes are exploring to the new cell this round, so we can
-            // detect collisions
-            let mut tmp_map: Grid<NumT, Territory> = Grid::new(*bounds);
-            for (to_expand, node) in frontier {
-                for maybe_neighbour in map.neighbours(to_expand) {
-                    match maybe_neighbour {
-                        Some(neighbour) => {
-                            if map.get(neighbour).is_none() {
-                                match &tmp_map.get(neighbour) {
-                                    Some(Territory::Node(n)) if node == *n => (),
-                                    Some(Territory::Node(_)) => {
-                                        // conflict
-                                        tmp_map.set(neighbour, Territory::Conflict);
-                                    }
-                                    Some(Territory::Conflict) => (),
-                                    None => {
-                                        // new territory
-                                        tmp_map.set(neighbour, Territory::Node(node));
-                                    }
-                                }
-                            }
-                        }
-                        None => {
-                            // no neighbour in this direction, must have hit edge => infinite area
-                            infinite.insert(node);
-                        }
-                    }
-                }
-            }
-
-            let mut new_frontier = vec![];
-            for (i, val) in tmp_map.into_iter() {
-                if let Territory::Node(node) = val {
-                    new_frontier.push((i, node));
-                }
-                map.set(i, val);
+        // each cell belongs to whichever node is strictly closest, and cells
+        // tied between nodes belong to none, even where a flood out from the
+        // nodes would reach one of them first
+        let tree = KdTree::new(nodes);
+        let mut map: Grid<NumT, usize> = Grid::new(*bounds);
+        for coord in bounds.coords() {
+            if let Some(node) = tree.nearest_unique(&coord, Metric::Manhattan) {
+                let i = map.coord_to_index(&coord);
+                map.set(i, node);
             }
-            frontier = new_frontier;
         }
 
-        // now tally the results
-        let mut bounded: HashMap<usize, usize> = HashMap::new();
-        for (_i, owner) in map.iter() {
-            match owner {
-                Territory::Node(id) => {
-                    if !infinite.contains(id) {
-                        *bounded.entry(*id).or_default() += 1;
-                    }
-                }
-                Territory::Conflict => (),
-            }
-        }
-
-        // and return the highest
-        *bounded.values().max().unwrap()
+        // with manhattan distance, the owner of a cell on the edge of the map
+        // also owns every cell further out in that direction, so areas
+        // touching the edge are infinite; return the largest of the rest
+        let regions = map.label_regions(Connectivity::Four, |_, owner| *owner);
+        regions.bounded().map(|r| r.size).max().unwrap()
     }
 
     fn part2((nodes, bounds): &(Vec<CoordT>, BoundsT), target_distance: usize) -> usize {
-        let mut map: Grid<NumT, ()> = Grid::new(*bounds);
+        let map: Grid<NumT, ()> = Grid::new(*bounds);
 
-        let middle = Coord::new(
-            bounds.left + (bounds.right - bounds.left) / 2,
-            bounds.top + (bounds.bottom - bounds.top) / 2,
-        );
-        let middle_index = map.coord_to_index(&middle);
+        let middle_index = map.coord_to_index(&bounds.centre());
 
         // start exploring in the middle, and explore the area that fits the constraints
-        let mut winners = 0;
-        let mut to_explore: Vec<usize> = vec![middle_index];
-
-        while let Some(curr) = to_explore.pop() {
-            if map.get(curr).is_none() {
-                map.set(curr, ());
-
-                let coord = map.index_to_coord(curr);
-
-                let score: usize = nodes
-                    .iter()
-                    .map(|c| coord.manhattan_distance(c) as usize)
-                    .sum();
-                if score < target_distance {
-                    winners += 1;
-
-                    for maybe_neighbour in map.neighbours(curr) {
-                        match maybe_neighbour {
-                            Some(neighbour) => to_explore.push(neighbour),
-                            None => (),
-                        }
-                    }
-                }
-            }
-        }
-
-        winners
+        map.flood_fill(middle_index, Connectivity::Four, |i, _| {
+            let coord = map.index_to_coord(i);
+            let score: usize = nodes
+                .iter()
+                .map(|c| coord.manhattan_distance(c) as usize)
+                .sum();
+            score < target_distance
+        })
+        .len()
     }
 }
 
-enum Territory {
-    Node(usize),
-    Conflict,
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -143,6 +68,15 @@ mod tests {
         assert_eq!(result, 17);
     }
 
+    #[test]
+    fn test_part1_ties() {
+        // (2, 3) and (4, 1) are as close to (2, 1) as to (3, 2), so the area
+        // around (3, 2) is 6 and not 8 as a flood from each node would have it
+        let input = "2, 1\n6, 5\n7, 0\n0, 5\n3, 2";
+        let result = Day::part1(&Day::parse(input.to_string()), ());
+        assert_eq!(result, 6);
+    }
+
     #[test]
     fn test_part1_solution() {
         let result = Day::part1(&Day::parse_input_file(), ());
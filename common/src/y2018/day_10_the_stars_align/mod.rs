@@ -2,7 +2,7 @@ use std::cmp;
 
 use crate::{
     interface::AoC,
-    spatial::{Bounds, Coord},
+    spatial::{ocr, Bounds, Coord, Grid},
 };
 
 use lazy_static::lazy_static;
@@ -20,7 +20,9 @@ impl AoC<Vec<Point>, String, NumT> for Day {
     }
 
     fn part1(points: &Vec<Point>) -> String {
-        Point::find_word(points)
+        // the example is drawn in a font the OCR doesn't know, so fall back to the raw art
+        let art = Point::find_word(points);
+        ocr::recognise_str(&art).unwrap_or(art)
     }
 
     fn part2(points: &Vec<Point>) -> NumT {
@@ -66,23 +68,11 @@ impl Point {
         let coords: Vec<CoordT> = convergence.into_iter().map(|p| p.position).collect();
 
         // build an output string
-        let bounds = Bounds::calculate(&coords);
-        let width = bounds.width + 1;
-        let height = bounds.height;
-
-        let mut chars: Vec<char> = vec!['.'; (width * height) as usize];
+        let mut grid: Grid<NumT, ()> = Grid::new(Bounds::calculate(&coords));
         for coord in &coords {
-            let i = (coord.y - bounds.top) * width + (coord.x - bounds.left);
-            chars[i as usize] = '#';
-        }
-
-        // add newlines
-        for y in 0..height {
-            let i = y * width + width - 1;
-            chars[i as usize] = '\n';
+            grid.set(grid.coord_to_index(coord), ());
         }
-
-        chars.into_iter().collect()
+        grid.render(|val| if val.is_some() { '#' } else { '.' })
     }
 
     fn find_convergence_arrangement(initial_points: &[Self]) -> (Vec<Self>, NumT) {
@@ -143,19 +133,6 @@ mod tests {
         #...#..###
     "};
 
-    const REAL_SOLUTION: &'static str = indoc! {"
-        ..##....#####....####...#....#.....###..#####...#....#..######
-        .#..#...#....#..#....#..#....#......#...#....#..#....#..#.....
-        #....#..#....#..#........#..#.......#...#....#...#..#...#.....
-        #....#..#....#..#........#..#.......#...#....#...#..#...#.....
-        #....#..#####...#.........##........#...#####.....##....#####.
-        ######..#....#..#..###....##........#...#....#....##....#.....
-        #....#..#....#..#....#...#..#.......#...#....#...#..#...#.....
-        #....#..#....#..#....#...#..#...#...#...#....#...#..#...#.....
-        #....#..#....#..#...##..#....#..#...#...#....#..#....#..#.....
-        #....#..#####....###.#..#....#...###....#####...#....#..#.....
-    "};
-
     #[test]
     fn test_part1_example() {
         let result = Day::part1(&Day::parse_example_file());
@@ -165,7 +142,7 @@ mod tests {
     #[test]
     fn test_part1_solution() {
         let result = Day::part1(&Day::parse_input_file());
-        assert_eq!(result, REAL_SOLUTION);
+        assert_eq!(result, "ABGXJBXF");
     }
 
     #[test]
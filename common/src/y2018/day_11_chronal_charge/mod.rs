@@ -1,6 +1,6 @@
 use std::ops::RangeInclusive;
 
-use crate::interface::AoC;
+use crate::{interface::AoC, spatial::*};
 
 pub struct Day;
 impl AoC<usize, (i32, (usize, usize, usize)), (i32, (usize, usize, usize))> for Day {
@@ -32,56 +32,28 @@ const WIDTH: usize = 300;
 const STARTING_SIZE: usize = 14; // based on examples
 const HALT_THRESHOLD: i32 = 20;
 
-fn calculate_powers(serial_number: usize) -> [[i32; WIDTH]; WIDTH] {
-    let mut powers = [[0; WIDTH]; WIDTH];
-    for x in 0..WIDTH {
-        for y in 0..WIDTH {
-            let power = power_level(x + 1, y + 1, serial_number);
-            powers[x][y] = power;
-        }
-    }
-    powers
+fn calculate_powers(serial_number: usize) -> SummedAreaTable<usize> {
+    let grid: Grid<usize, ()> = Grid::new(Bounds::new(1, WIDTH, 1, WIDTH));
+    grid.summed_area_table(|i, _| {
+        let coord = grid.index_to_coord(i);
+        power_level(coord.x, coord.y, serial_number) as i64
+    })
 }
 
 fn best_square_of_size(
-    powers: &[[i32; WIDTH]; WIDTH],
+    powers: &SummedAreaTable<usize>,
     size: usize,
 ) -> (i32, (usize, usize, usize)) {
-    let mut rolling_col = [0; WIDTH];
-
     let mut best_score = i32::MIN;
     let mut best_coord = (0, 0, 0);
 
-    for x in 0..WIDTH {
-        let add_col = &powers[x];
-        let sub_col = if x >= size {
-            Some(&powers[x - size])
-        } else {
-            None
-        };
-        let check_best = x >= (size - 1);
-
-        for y in 0..WIDTH {
-            rolling_col[y] += add_col[y];
-
-            if let Some(col) = sub_col {
-                rolling_col[y] -= col[y];
-            }
-        }
-
-        if check_best {
-            let mut rolling_val = 0;
-            for y in 0..WIDTH {
-                rolling_val += rolling_col[y];
-
-                if y >= size {
-                    rolling_val -= rolling_col[y - size];
-                }
-
-                if y >= (size - 1) && rolling_val > best_score {
-                    best_score = rolling_val;
-                    best_coord = (x + 2 - size, y + 2 - size, size);
-                }
+    for x in 1..=(WIDTH + 1 - size) {
+        for y in 1..=(WIDTH + 1 - size) {
+            let square = Bounds::new(x, x + size - 1, y, y + size - 1);
+            let score = powers.sum(&square) as i32;
+            if score > best_score {
+                best_score = score;
+                best_coord = (x, y, size);
             }
         }
     }
@@ -90,7 +62,7 @@ fn best_square_of_size(
 }
 
 fn best_square_dynamic_size(
-    powers: &[[i32; WIDTH]; WIDTH],
+    powers: &SummedAreaTable<usize>,
     sizes: RangeInclusive<usize>,
 ) -> (i32, (usize, usize, usize)) {
     let mut best = (i32::MIN, (0, 0, 0));
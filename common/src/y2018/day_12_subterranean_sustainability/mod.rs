@@ -1,4 +1,4 @@
-use crate::{interface::AoC, math::*};
+use crate::{cycle::offset_at, interface::AoC, math::bit_set::BitSet};
 
 use lazy_static::lazy_static;
 use regex::Regex;
@@ -23,6 +23,7 @@ impl AoC<Input, isize, isize> for Day {
 }
 
 type Notes = [bool; 32];
+type Plants = BitSet<4>;
 
 #[derive(Debug)]
 pub struct Input {
@@ -86,9 +87,9 @@ impl Input {
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 struct Simulation {
-    state: U256,
+    state: Plants,
     width: u8,
     start_index: isize,
 }
@@ -97,41 +98,30 @@ impl Simulation {
     fn initial_state(input: &Input) -> Self {
         let (state, width) = input.initial;
         Self {
-            state: U256::from_u128(state),
+            state: Plants::from_u128(state),
             start_index: 0,
             width,
         }
     }
 
     fn run(input: &Input, num_generations: usize) -> Self {
-        let mut sim = Self::initial_state(input);
-        let plant_mask = U256::from_u128(31);
-
-        for generation in 1..=num_generations {
-            let next_sim = Self::tick(&sim, &input.notes, &plant_mask);
-
-            if sim.state == next_sim.state {
-                // we found a repeating pattern!
-                assert_eq!(sim.width, next_sim.width);
-
-                let index_diff = next_sim.start_index - sim.start_index;
-                let remaining = (num_generations - generation) as isize;
-                let start_index = next_sim.start_index + (remaining * index_diff);
-
-                return Self {
-                    state: sim.state,
-                    width: sim.width,
-                    start_index,
-                };
-            } else {
-                sim = next_sim;
-            }
+        let plant_mask = Plants::from_u128(31);
+
+        // the pattern eventually repeats, drifting along by the same amount
+        // each time
+        let (sim, start_index) = offset_at(
+            Self::initial_state(input),
+            |sim| Self::tick(sim, &input.notes, &plant_mask),
+            |sim| ((sim.state, sim.width), sim.start_index as i128),
+            num_generations,
+        );
+        Self {
+            start_index: start_index as isize,
+            ..sim
         }
-
-        sim
     }
 
-    fn tick(prev: &Self, notes: &Notes, plant_mask: &U256) -> Self {
+    fn tick(prev: &Self, notes: &Notes, plant_mask: &Plants) -> Self {
         // we'll want to visit numbers two on either side of the prev generation
         // #..#.#
         //      ^^^^^
@@ -144,7 +134,7 @@ impl Simulation {
         //      m
         // so start with prev generation shifted 4 left so it aligns with the mask
         let mut prev_state = prev.state << 4;
-        let mut new_state: U256 = U256::new();
+        let mut new_state = Plants::new();
 
         let mut new_width = prev.width + 4;
         let mut new_start_index = prev.start_index - 2;
@@ -160,7 +150,7 @@ impl Simulation {
             new_state >>= 1;
 
             if notes[i] {
-                new_state |= U256::LEFTMOST_BIT;
+                new_state.set(Plants::BITS - 1, true);
             }
 
             // bump prev state to the right
@@ -168,20 +158,17 @@ impl Simulation {
         }
 
         // trim left side of any extra zeroes
-        while (new_state & U256::LEFTMOST_BIT) == 0 {
-            new_state <<= 1;
-            new_width -= 1;
-            new_start_index += 1;
-        }
+        let leading = new_state.leading_zeros();
+        new_width -= leading as u8;
+        new_start_index += leading as isize;
 
         // shift to right side of int
-        new_state >>= 256 - new_width as usize;
+        new_state >>= Plants::BITS - leading - new_width as usize;
 
         // trim right side of any extra zeroes
-        while (new_state & U256::ONE) == 0 {
-            new_state >>= 1;
-            new_width -= 1;
-        }
+        let trailing = new_state.trailing_zeros();
+        new_state >>= trailing;
+        new_width -= trailing as u8;
 
         Self {
             state: new_state,
@@ -191,17 +178,8 @@ impl Simulation {
     }
 
     fn sum_plant_indices(&self) -> isize {
-        let mut sum = 0;
-        let mut state = self.state;
-        let mut index = self.start_index + self.width as isize - 1;
-        for _ in 0..self.width {
-            if (state & U256::ONE) == 1 {
-                sum += index;
-            }
-            state >>= 1;
-            index -= 1;
-        }
-        sum
+        let last_index = self.start_index + self.width as isize - 1;
+        self.state.iter().map(|bit| last_index - bit as isize).sum()
     }
 }
 